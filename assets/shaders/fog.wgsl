@@ -2,6 +2,16 @@
 #import bevy_pbr::mesh_bindings
 #import bevy_pbr::mesh_functions
 
+struct FogParams {
+    density: f32,
+    time: f32,
+    wasm_padding_1: f32,
+    wasm_padding_2: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> params: FogParams;
+
 // helper functions
 
 fn permute_4(x: vec4<f32>) -> vec4<f32> {
@@ -55,8 +65,8 @@ fn noise(position: vec2<f32>) -> f32 {
     let lf_position = position * low_frequency;
     let hf_position = position * high_frequency;
 
-    let lf_offset = vec2<f32>(-0.8 * globals.time, -1.2 * globals.time) * lf_speed;
-    let hf_offset = vec2<f32>(-1.1 * globals.time, -0.9 * globals.time) * hf_speed;
+    let lf_offset = vec2<f32>(-0.8 * params.time, -1.2 * params.time) * lf_speed;
+    let hf_offset = vec2<f32>(-1.1 * params.time, -0.9 * params.time) * hf_speed;
 
     let lf_noise = (perlin_noise(lf_position + lf_offset) + 1.0) / 2.0;
 
@@ -108,5 +118,6 @@ let color_2 = vec3<f32>(0.10, 0.20, 0.30);
 @fragment
 fn fragment(in: Fragment) -> @location(0) vec4<f32> {
     let noise = noise(in.position_2d);
-    return vec4<f32>(mix(color_2, color_1, noise) * 1.3, 0.75);
+    let alpha = clamp(0.75 * params.density, 0.0, 1.0);
+    return vec4<f32>(mix(color_2, color_1, noise) * 1.3, alpha);
 }
@@ -0,0 +1,342 @@
+use std::time::Duration;
+
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState, InputSystem},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::Deserialize;
+
+use crate::settings::Settings;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Actions>()
+            .add_system_to_stage(CoreStage::PreUpdate, read_keyboard.after(InputSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, read_gamepads.after(read_keyboard));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum Action {
+    Move(Direction),
+    Dash,
+    Confirm,
+    Pause,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn vec(self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::Y,
+            Direction::Down => IVec2::NEG_Y,
+            Direction::Left => IVec2::NEG_X,
+            Direction::Right => IVec2::X,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Device {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButtonType),
+    // triggers past the value, towards its sign
+    Axis(GamepadAxisType, f32),
+}
+
+impl Binding {
+    fn device(self) -> Device {
+        match self {
+            Binding::Key(_) => Device::Keyboard,
+            Binding::Button(_) | Binding::Axis(..) => Device::Gamepad,
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Binding::Key(key_code) => {
+                let name = format!("{:?}", key_code);
+                match name.len() {
+                    1 => name,
+                    _ => name.to_lowercase(),
+                }
+            }
+            Binding::Button(button_type) => match button_type {
+                GamepadButtonType::South => "A".to_string(),
+                GamepadButtonType::East => "B".to_string(),
+                GamepadButtonType::West => "X".to_string(),
+                GamepadButtonType::North => "Y".to_string(),
+                GamepadButtonType::DPadUp
+                | GamepadButtonType::DPadDown
+                | GamepadButtonType::DPadLeft
+                | GamepadButtonType::DPadRight => "d-pad".to_string(),
+                _ => format!("{:?}", button_type).to_lowercase(),
+            },
+            Binding::Axis(axis_type, _) => match axis_type {
+                GamepadAxisType::LeftStickX | GamepadAxisType::LeftStickY => "left stick",
+                GamepadAxisType::RightStickX | GamepadAxisType::RightStickY => "right stick",
+                _ => "axis",
+            }
+            .to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    // any gamepad if none
+    pub gamepad: Option<usize>,
+    actions: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use self::{Binding::*, Direction::*};
+        use GamepadAxisType::*;
+        use GamepadButtonType::*;
+        let map = [
+            (
+                Action::Move(Up),
+                vec![
+                    Key(KeyCode::Up),
+                    Key(KeyCode::W),
+                    Button(DPadUp),
+                    Axis(LeftStickY, 0.5),
+                ],
+            ),
+            (
+                Action::Move(Down),
+                vec![
+                    Key(KeyCode::Down),
+                    Key(KeyCode::S),
+                    Button(DPadDown),
+                    Axis(LeftStickY, -0.5),
+                ],
+            ),
+            (
+                Action::Move(Left),
+                vec![
+                    Key(KeyCode::Left),
+                    Key(KeyCode::A),
+                    Button(DPadLeft),
+                    Axis(LeftStickX, -0.5),
+                ],
+            ),
+            (
+                Action::Move(Right),
+                vec![
+                    Key(KeyCode::Right),
+                    Key(KeyCode::D),
+                    Button(DPadRight),
+                    Axis(LeftStickX, 0.5),
+                ],
+            ),
+            (Action::Dash, vec![Key(KeyCode::LShift), Button(East)]),
+            (
+                Action::Confirm,
+                vec![Key(KeyCode::Space), Key(KeyCode::Return), Button(South)],
+            ),
+            (
+                Action::Pause,
+                vec![Key(KeyCode::Escape), Key(KeyCode::P), Button(Start)],
+            ),
+            (Action::Quit, vec![Key(KeyCode::Q), Button(Select)]),
+        ];
+        Self {
+            gamepad: None,
+            actions: map.into_iter().collect(),
+        }
+    }
+}
+
+impl Bindings {
+    fn get(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    fn label(&self, action: Action, device: Device) -> String {
+        let Action::Move(_) = action else {
+            return self
+                .first(action, device)
+                .map_or("?".to_string(), Binding::label);
+        };
+        let directions = [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+        ];
+        let bindings = directions.map(|direction| self.first(Action::Move(direction), device));
+        let arrows = [KeyCode::Up, KeyCode::Left, KeyCode::Down, KeyCode::Right].map(Binding::Key);
+        if bindings == arrows.map(Some) {
+            return "arrows".to_string();
+        }
+        let mut labels: Vec<String> = bindings
+            .into_iter()
+            .map(|binding| binding.map_or("?".to_string(), Binding::label))
+            .collect();
+        labels.dedup();
+        match labels.iter().all(|label| label.len() == 1) {
+            true => labels.concat(),
+            false => labels.join("/"),
+        }
+    }
+
+    fn first(&self, action: Action, device: Device) -> Option<Binding> {
+        let bindings = self.get(action);
+        let on_device = bindings.iter().find(|binding| binding.device() == device);
+        on_device.or(bindings.first()).copied()
+    }
+
+    fn drives(&self, gamepad: Gamepad) -> bool {
+        self.gamepad.is_none() || self.gamepad == Some(gamepad.id)
+    }
+}
+
+pub fn label(players: &[Bindings], action: Action, device: Device) -> String {
+    let mut labels: Vec<String> = Vec::new();
+    for bindings in players {
+        let label = bindings.label(action, device);
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels.join(" | ")
+}
+
+#[derive(Resource, Default)]
+pub struct Actions {
+    pressed: Vec<(usize, Action)>,
+    pub device: Device,
+    pub time: Duration,
+}
+
+impl Actions {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.iter().any(|&(_, pressed)| pressed == action)
+    }
+
+    pub fn iter(&self, player: usize) -> impl Iterator<Item = Action> + '_ {
+        self.pressed
+            .iter()
+            .filter(move |&&(index, _)| index == player)
+            .map(|&(_, action)| action)
+    }
+
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+    }
+
+    fn press(&mut self, player: usize, action: Action, device: Device) {
+        self.pressed.push((player, action));
+        self.device = device;
+    }
+}
+
+fn read_keyboard(
+    mut actions: ResMut<Actions>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut held_keys: Local<HashSet<KeyCode>>,
+) {
+    actions.clear();
+    actions.time = time.elapsed();
+    // keyboard events keep the order of presses within a frame
+    for event in keyboard_events.iter() {
+        let Some(key_code) = event.key_code else {
+            continue;
+        };
+        // ignores the repeated presses of held keys
+        if event.state == ButtonState::Released {
+            held_keys.remove(&key_code);
+            continue;
+        }
+        if !held_keys.insert(key_code) {
+            continue;
+        }
+        for (player, bindings) in settings.bindings.iter().enumerate() {
+            for (&action, action_bindings) in &bindings.actions {
+                if action_bindings.contains(&Binding::Key(key_code)) {
+                    actions.press(player, action, Device::Keyboard);
+                }
+            }
+        }
+    }
+}
+
+fn read_gamepads(
+    mut actions: ResMut<Actions>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    settings: Res<Settings>,
+    mut tilted_axes: Local<HashSet<(Gamepad, GamepadAxisType, bool)>>,
+) {
+    let players = &settings.bindings;
+    // axes are tilted once for every player bound to them
+    let mut tilted = HashSet::new();
+    for gamepad in gamepads.iter() {
+        for action_bindings in players
+            .iter()
+            .flat_map(|bindings| bindings.actions.values())
+        {
+            for &binding in action_bindings {
+                let Binding::Axis(axis_type, threshold) = binding else {
+                    continue;
+                };
+                let value = axes
+                    .get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or(0.0);
+                let past = match threshold >= 0.0 {
+                    true => value >= threshold,
+                    false => value <= threshold,
+                };
+                if past {
+                    tilted.insert((gamepad, axis_type, threshold >= 0.0));
+                }
+            }
+        }
+    }
+    for (player, bindings) in players.iter().enumerate() {
+        for gamepad in gamepads.iter().filter(|&gamepad| bindings.drives(gamepad)) {
+            for (&action, action_bindings) in &bindings.actions {
+                for &binding in action_bindings {
+                    let pressed = match binding {
+                        Binding::Key(_) => false,
+                        Binding::Button(button_type) => {
+                            buttons.just_pressed(GamepadButton::new(gamepad, button_type))
+                        }
+                        Binding::Axis(axis_type, threshold) => {
+                            let key = (gamepad, axis_type, threshold >= 0.0);
+                            tilted.contains(&key) && !tilted_axes.contains(&key)
+                        }
+                    };
+                    if pressed {
+                        actions.press(player, action, Device::Gamepad);
+                    }
+                }
+            }
+        }
+    }
+    *tilted_axes = tilted;
+}
@@ -1,21 +1,29 @@
+// the shader type derive leaves unused checks behind on newer compilers
+#![allow(dead_code)]
+
 use bevy::{
     pbr::{NotShadowCaster, NotShadowReceiver},
     prelude::*,
     reflect::TypeUuid,
     render::{
         mesh::Indices,
-        render_resource::{AsBindGroup, PrimitiveTopology, ShaderRef},
+        render_resource::{AsBindGroup, PrimitiveTopology, ShaderRef, ShaderType},
     },
 };
 
-use crate::board::HIDDEN_HEIGHT;
+use crate::{
+    board::HIDDEN_HEIGHT,
+    clock::{Clock, Clocks},
+    track::{self, Choreography},
+};
 
 pub struct FogPlugin;
 
 impl Plugin for FogPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(MaterialPlugin::<FogMaterial>::default())
-            .add_startup_system(setup);
+            .add_startup_system(setup)
+            .add_system(animate.after(track::mix));
     }
 }
 
@@ -36,16 +44,55 @@ fn setup(
     ));
 }
 
+// the fog flows with the background clock, so it keeps its pace when the trial slows down
+fn animate(
+    query: Query<&Handle<FogMaterial>>,
+    mut materials: ResMut<Assets<FogMaterial>>,
+    choreography: Res<Choreography>,
+    clocks: Res<Clocks>,
+) {
+    let time = clocks.get(Clock::Background).elapsed().as_secs_f32();
+    for handle in &query {
+        let Some(material) = materials.get(handle) else {
+            continue;
+        };
+        // avoids preparing the material again while the clock is paused
+        if material.params.density != choreography.fog || material.params.time != time {
+            let material = materials.get_mut(handle).unwrap();
+            material.params.density = choreography.fog;
+            material.params.time = time;
+        }
+    }
+}
+
 #[derive(Clone, AsBindGroup, TypeUuid, Debug)]
 #[uuid = "fec7aad3-dd4b-43d6-be0b-a56cf4349038"]
-struct FogMaterial {}
+struct FogMaterial {
+    #[uniform(0)]
+    params: FogParams,
+}
 
 impl FogMaterial {
     fn new() -> Self {
-        Self {}
+        Self {
+            params: FogParams {
+                density: 1.0,
+                time: 0.0,
+                wasm_padding_1: 0.0,
+                wasm_padding_2: 0.0,
+            },
+        }
     }
 }
 
+#[derive(Clone, ShaderType, Debug)]
+struct FogParams {
+    density: f32,
+    time: f32,
+    wasm_padding_1: f32,
+    wasm_padding_2: f32,
+}
+
 impl Material for FogMaterial {
     fn alpha_mode(&self) -> AlphaMode {
         AlphaMode::Blend
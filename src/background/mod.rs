@@ -8,7 +8,13 @@ use bevy::{
     prelude::*,
 };
 
-use crate::{material_from_color, palette, AppState};
+use crate::{
+    board::{self, Board},
+    clock::{Clock, Clocks},
+    material_from_color, palette,
+    trial::{CurrentTrial, Trial},
+    AfterTeardown, AppState,
+};
 
 use self::{fog::FogPlugin, screen::ScreenPlugin};
 
@@ -21,8 +27,12 @@ impl Plugin for BackgroundPlugin {
         app.add_plugin(FogPlugin)
             .add_plugin(ScreenPlugin)
             .insert_resource(Countdown::new())
+            .add_event::<Seek>()
             .add_startup_system(setup)
-            .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(enter_setup))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(enter_setup.after(board::enter_setup)),
+            )
             .add_system_set(
                 SystemSet::on_update(AppState::Setup)
                     .with_system(countdown)
@@ -32,7 +42,9 @@ impl Plugin for BackgroundPlugin {
             .add_system_set(
                 SystemSet::on_update(AppState::Game)
                     .with_system(countdown)
-                    .with_system(transition.after(countdown)),
+                    .with_system(scrub.after(countdown))
+                    .with_system(seek.after(scrub))
+                    .with_system(transition.after(seek)),
             )
             .add_system_set(SystemSet::on_enter(AppState::Defeat).with_system(enter_defeat))
             .add_system_set(SystemSet::on_enter(AppState::Victory).with_system(enter_victory))
@@ -102,12 +114,17 @@ fn setup(
     ));
 }
 
-fn enter_setup(mut countdown: ResMut<Countdown>) {
-    countdown.reset(1.5, Some(AppState::Start));
+fn enter_setup(mut countdown: ResMut<Countdown>, board: Res<Board>) {
+    countdown.reset(board.wave_duration(), Some(AppState::Start));
 }
 
-fn enter_start(mut countdown: ResMut<Countdown>) {
-    countdown.reset(20.0, Some(AppState::Victory));
+fn enter_start(
+    mut countdown: ResMut<Countdown>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+) {
+    let trial = trials.get(&current_trial.handle).unwrap();
+    countdown.reset(trial.duration, Some(AppState::Victory));
 }
 
 fn enter_defeat(asset_server: Res<AssetServer>, audio: Res<Audio>) {
@@ -125,8 +142,12 @@ fn enter_victory(
     audio.play_with_settings(sound, PlaybackSettings::ONCE.with_volume(0.3));
 }
 
-fn enter_teardown(mut countdown: ResMut<Countdown>) {
-    countdown.reset(1.5, Some(AppState::Setup));
+fn enter_teardown(
+    mut countdown: ResMut<Countdown>,
+    board: Res<Board>,
+    after_teardown: Res<AfterTeardown>,
+) {
+    countdown.reset(board.wave_duration(), Some(after_teardown.state));
 }
 
 fn exit_teardown(asset_server: Res<AssetServer>, audio: Res<Audio>) {
@@ -153,10 +174,47 @@ impl Countdown {
         self.timer.reset();
         self.transition = transition;
     }
+
+    pub fn seek(&mut self, time: Duration) {
+        // resetting clears the finished flag when seeking backwards
+        self.timer.reset();
+        self.timer.set_elapsed(time.min(self.timer.duration()));
+    }
+}
+
+pub struct Seek {
+    pub time: Duration,
 }
 
-pub fn countdown(mut countdown: ResMut<Countdown>, time: Res<Time>) {
-    countdown.timer.tick(time.delta());
+impl Seek {
+    pub fn new(time: Duration) -> Self {
+        Self { time }
+    }
+}
+
+pub fn countdown(mut countdown: ResMut<Countdown>, clocks: Res<Clocks>) {
+    countdown.timer.tick(clocks.get(Clock::Trial).delta());
+}
+
+pub fn seek(mut events: EventReader<Seek>, mut countdown: ResMut<Countdown>) {
+    for event in events.iter() {
+        countdown.seek(event.time);
+    }
+}
+
+// debug builds can jump a second backwards or forwards with [,] and [.]
+fn scrub(input: Res<Input<KeyCode>>, countdown: Res<Countdown>, mut events: EventWriter<Seek>) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let elapsed = countdown.timer.elapsed();
+    let step = Duration::from_secs(1);
+    if input.just_pressed(KeyCode::Comma) {
+        events.send(Seek::new(elapsed.saturating_sub(step)));
+    }
+    if input.just_pressed(KeyCode::Period) {
+        events.send(Seek::new(elapsed + step));
+    }
 }
 
 fn transition(countdown: Res<Countdown>, mut state: ResMut<State<AppState>>) {
@@ -14,8 +14,15 @@ use bevy::{
 };
 
 use crate::{
+    actions::{self, Action, Actions, Direction},
     background::{self, Countdown},
-    material_from_color, palette, AppState,
+    campaign::Campaign,
+    clock::{Clock, Clocks},
+    material_from_color, palette,
+    player::{self, Dash, Player, Winner},
+    settings::Settings,
+    trial::{CurrentTrial, Trial},
+    AppState,
 };
 
 pub struct ScreenPlugin;
@@ -26,9 +33,13 @@ impl Plugin for ScreenPlugin {
             .add_startup_system(setup)
             .add_system_set(
                 SystemSet::on_update(AppState::Game)
-                    .with_system(show_countdown.after(background::countdown)),
+                    .with_system(show_countdown.after(background::countdown))
+                    .with_system(show_dash),
             )
+            .add_system_set(SystemSet::on_enter(AppState::Victory).with_system(show_winner))
             .add_system(show_screen_elements)
+            .add_system(show_trial)
+            .add_system(show_prompts)
             .add_system(flip)
             .add_system(spin);
     }
@@ -152,6 +163,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Splash),
+        TrialText,
     );
     let splash_text = (
         TextBundle {
@@ -177,7 +189,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
     let splash_bottom_text = (
         TextBundle {
             text: Text::from_section(
-                "[space] to start",
+                " ",
                 TextStyle {
                     font: asset_server.load("fonts/roboto_bold.ttf"),
                     font_size: 75.0,
@@ -191,6 +203,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Splash),
+        Prompt::new(Action::Confirm, "to start"),
     );
     let start_top_text = (
         TextBundle {
@@ -209,6 +222,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Start),
+        TrialText,
     );
     let dodge_text = (
         TextBundle {
@@ -231,7 +245,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
     let start_bottom_text = (
         TextBundle {
             text: Text::from_section(
-                "[arrows] to move",
+                " ",
                 TextStyle {
                     font: asset_server.load("fonts/roboto_bold.ttf"),
                     font_size: 75.0,
@@ -245,6 +259,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Start),
+        Prompt::new(Action::Move(Direction::Up), "to move"),
     );
     let countdown_text = (
         TextBundle {
@@ -261,6 +276,21 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
         ScreenElement::new(AppState::Game),
         CountdownText,
     );
+    let dash_text = (
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/roboto_bold.ttf"),
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                },
+            ),
+            ..default()
+        },
+        ScreenElement::new(AppState::Game),
+        DashText,
+    );
     let gear = (
         ImageBundle {
             style: Style {
@@ -306,7 +336,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
     let defeat_bottom_text = (
         TextBundle {
             text: Text::from_section(
-                "[space] to retry",
+                " ",
                 TextStyle {
                     font: asset_server.load("fonts/roboto_bold.ttf"),
                     font_size: 75.0,
@@ -320,6 +350,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Defeat),
+        Prompt::new(Action::Confirm, "to retry"),
     );
     let victory_top_text = (
         TextBundle {
@@ -338,6 +369,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Victory),
+        VictoryText,
     );
     let popper = (
         ImageBundle {
@@ -355,7 +387,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
     let victory_bottom_text = (
         TextBundle {
             text: Text::from_section(
-                "[space] to replay",
+                " ",
                 TextStyle {
                     font: asset_server.load("fonts/roboto_bold.ttf"),
                     font_size: 75.0,
@@ -369,7 +401,59 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         },
         ScreenElement::new(AppState::Victory),
+        Prompt::new(Action::Confirm, "to continue"),
+    );
+    let paused_page = (
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Auto),
+                ..default()
+            },
+            ..default()
+        },
+        ScreenElement::new(AppState::Paused),
     );
+    let paused_text = TextBundle {
+        text: Text::from_section(
+            "Paused",
+            TextStyle {
+                font: asset_server.load("fonts/roboto_bold.ttf"),
+                font_size: 100.0,
+                color: Color::BLACK,
+            },
+        ),
+        style: Style {
+            margin: UiRect::new(
+                Val::Undefined,
+                Val::Undefined,
+                Val::Undefined,
+                Val::Px(15.0),
+            ),
+            ..default()
+        },
+        ..default()
+    };
+    // the page shows or hides its prompts along with it
+    let paused_prompts = [
+        (Action::Pause, "to resume"),
+        (Action::Confirm, "to restart"),
+        (Action::Quit, "to quit"),
+    ]
+    .map(|(action, text)| {
+        (
+            TextBundle::from_section(
+                " ",
+                TextStyle {
+                    font: asset_server.load("fonts/roboto_bold.ttf"),
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                },
+            ),
+            Prompt::new(action, text),
+        )
+    });
     let broom = (
         ImageBundle {
             style: Style {
@@ -390,6 +474,7 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
         builder.spawn(dodge_text);
         builder.spawn(start_bottom_text);
         builder.spawn(countdown_text);
+        builder.spawn(dash_text);
         builder.spawn(gear);
         builder.spawn(defeat_top_text);
         builder.spawn(skull);
@@ -397,13 +482,40 @@ fn screen_ui(commands: &mut Commands, asset_server: &AssetServer) {
         builder.spawn(victory_top_text);
         builder.spawn(popper);
         builder.spawn(victory_bottom_text);
+        builder.spawn(paused_page).with_children(|builder| {
+            builder.spawn(paused_text);
+            for prompt in paused_prompts {
+                builder.spawn(prompt);
+            }
+        });
         builder.spawn(broom);
     });
 }
 
+#[derive(Component)]
+struct TrialText;
+
+#[derive(Component)]
+struct VictoryText;
+
 #[derive(Component)]
 struct CountdownText;
 
+#[derive(Component)]
+struct DashText;
+
+#[derive(Component)]
+struct Prompt {
+    action: Action,
+    text: &'static str,
+}
+
+impl Prompt {
+    pub fn new(action: Action, text: &'static str) -> Self {
+        Self { action, text }
+    }
+}
+
 #[derive(Component)]
 struct ScreenElement {
     state: AppState,
@@ -453,16 +565,66 @@ fn show_screen_elements(
     }
 }
 
-fn flip(mut query: Query<(&mut Flip, &mut UiImage)>, time: Res<Time>) {
-    for (mut flip, mut ui_image) in &mut query {
-        flip.timer.tick(time.delta());
-        ui_image.flip_x = flip.timer.percent() >= 0.5;
+fn show_prompts(
+    mut query: Query<(&Prompt, &mut Text)>,
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+) {
+    for (prompt, text) in &mut query {
+        let label = actions::label(&settings.bindings, prompt.action, actions.device);
+        update_text(text, format!("[{}] {}", label, prompt.text));
     }
 }
 
-fn spin(mut query: Query<(&mut Spin, &mut Transform)>, time: Res<Time>) {
+fn show_trial(
+    mut query: Query<&mut Text, With<TrialText>>,
+    current_trial: Res<CurrentTrial>,
+    campaign: Res<Campaign>,
+    trials: Res<Assets<Trial>>,
+) {
+    let Some(trial) = trials.get(&current_trial.handle) else {
+        return;
+    };
+    let number = current_trial.index + 1;
+    let value = format!("trial {}/{}: {}", number, campaign.trials.len(), trial.name);
+    for text in &mut query {
+        update_text(text, value.clone());
+    }
+}
+
+fn show_winner(
+    mut text_query: Query<&mut Text, With<VictoryText>>,
+    player_query: Query<&Player>,
+    winner: Res<Winner>,
+    settings: Res<Settings>,
+) {
+    let winner = player_query
+        .iter()
+        .find(|player| Some(player.index) == winner.index);
+    let versus = player::versus(&settings, player_query.iter().len());
+    let (value, color) = match (winner, versus) {
+        (Some(player), _) => (format!("P{} wins!", player.index + 1), player.color),
+        (None, true) => ("Draw!".to_string(), Color::BLACK),
+        (None, false) => ("Victory!".to_string(), Color::BLACK),
+    };
+    let mut text = text_query.single_mut();
+    text.sections[0].value = value;
+    text.sections[0].style.color = color;
+}
+
+fn flip(mut query: Query<(&mut Flip, &mut Transform)>, clocks: Res<Clocks>) {
+    for (mut flip, mut transform) in &mut query {
+        flip.timer.tick(clocks.get(Clock::Interface).delta());
+        transform.scale.x = match flip.timer.percent() >= 0.5 {
+            true => -1.0,
+            false => 1.0,
+        };
+    }
+}
+
+fn spin(mut query: Query<(&mut Spin, &mut Transform)>, clocks: Res<Clocks>) {
     for (mut spin, mut transform) in &mut query {
-        spin.timer.tick(time.delta());
+        spin.timer.tick(clocks.get(Clock::Interface).delta());
         transform.rotation = Quat::from_rotation_z(2.0 * PI * spin.timer.percent());
     }
 }
@@ -472,3 +634,32 @@ fn show_countdown(mut query: Query<&mut Text, With<CountdownText>>, countdown: R
     let remaining = countdown.timer.duration() - countdown.timer.elapsed();
     text.sections[0].value = format!("{:.1}", remaining.as_secs_f32());
 }
+
+fn show_dash(
+    mut text_query: Query<&mut Text, With<DashText>>,
+    player_query: Query<(&Player, &Dash)>,
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+) {
+    let mut dashes: Vec<(&Player, &Dash)> = player_query.iter().collect();
+    dashes.sort_by_key(|(player, _)| player.index);
+    let labels: Vec<String> = dashes
+        .into_iter()
+        .map(|(player, dash)| match dash.is_ready() {
+            true => {
+                let bindings = settings.bindings.get(player.index..=player.index);
+                let label = actions::label(bindings.unwrap_or(&[]), Action::Dash, actions.device);
+                format!("[{}] dash", label)
+            }
+            false => format!("dash in {:.1}", dash.remaining_secs()),
+        })
+        .collect();
+    update_text(text_query.single_mut(), labels.join("  "));
+}
+
+// avoids laying out the text again every frame
+fn update_text(mut text: Mut<Text>, value: String) {
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
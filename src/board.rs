@@ -1,84 +1,152 @@
 use bevy::{prelude::*, utils::HashSet};
+use serde::Deserialize;
 
 use crate::{
-    background, material_from_color, palette,
-    phases::{self, Phase, Phases},
+    clock::{Clock, Clocks},
+    easing::Easing,
+    material_from_color, palette,
+    phases::{self, Phase, Phases, PhasesPlugin},
+    timeline::Idle,
+    trial::{CurrentTrial, Trial},
     AppState,
 };
 
 pub const SHOWN_HEIGHT: f32 = 0.0;
 pub const HIDDEN_HEIGHT: f32 = -7.0;
+const STAGGER: f32 = 0.05;
 
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Board>()
-            .add_startup_system(setup)
+        app.add_plugin(PhasesPlugin::<BoardMode>::default())
+            .init_resource::<Board>()
             .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(enter_setup))
             .add_system_set(SystemSet::on_enter(AppState::Teardown).with_system(enter_teardown))
-            .add_system(phases::transition::<BoardMode>.after(background::countdown))
+            .add_system_set(SystemSet::on_exit(AppState::Teardown).with_system(exit_teardown))
             .add_system(to_world_xz)
+            .add_system(tween)
             .add_system(to_world_y.after(phases::transition::<BoardMode>));
     }
 }
 
-fn setup(
+pub fn enter_setup(
     mut board: ResMut<Board>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
 ) {
-    let model = MaterialMeshBundle {
-        mesh: meshes.add(Mesh::from(shape::Box::new(0.92, 40.0, 0.92))),
-        material: materials.add(material_from_color(palette::DARK_BLACK)),
-        transform: Transform::from_xyz(0.0, -20.0, 0.0),
-        ..default()
-    };
-    for i in -1..=1 {
-        for j in -1..=1 {
-            let root = (
-                SpatialBundle::from_transform(Transform::from_xyz(
-                    j as f32,
-                    HIDDEN_HEIGHT,
-                    -i as f32,
-                )),
-                Tile,
-                Phases::new(BoardMode::Hidden),
-            );
-            commands.spawn(root).with_children(|builder| {
-                builder.spawn(model.clone());
-            });
-            board.tiles.insert(IVec2::new(j, i));
-        }
-    }
-}
-
-fn enter_setup(mut query: Query<(&Transform, &mut Phases<BoardMode>), With<Tile>>) {
-    for (transform, mut phases) in &mut query {
-        let offset = transform.translation.x + transform.translation.z;
+    *board = trials.get(&current_trial.handle).unwrap().board.clone();
+    let mesh = meshes.add(Mesh::from(shape::Box::new(0.92, 40.0, 0.92)));
+    for &tile in &board.tiles {
+        // every tile gets its own material, to be tinted on its own
+        let model = commands
+            .spawn(MaterialMeshBundle {
+                mesh: mesh.clone(),
+                material: materials.add(material_from_color(palette::DARK_BLACK)),
+                transform: Transform::from_xyz(0.0, -20.0, 0.0),
+                ..default()
+            })
+            .id();
+        let mut phases = Phases::new(BoardMode::Hidden);
         phases.reset(vec![
-            Phase::new(BoardMode::Waiting, 0.4 + offset as f32 * 0.05),
-            Phase::new(BoardMode::Entering, 1.0),
+            Phase::new(BoardMode::Waiting, 0.3 + board.stagger(tile)),
+            Phase::new(BoardMode::Entering, 1.0).with_easing(Easing::BackInOut),
             Phase::new(BoardMode::Shown, 0.0), // final
         ]);
+        let root = (
+            SpatialBundle::from_transform(Transform::from_xyz(
+                tile.x as f32,
+                HIDDEN_HEIGHT,
+                -tile.y as f32,
+            )),
+            Position::new(tile),
+            Tile::new(model),
+            phases,
+        );
+        commands.spawn(root).push_children(&[model]);
     }
 }
 
-fn enter_teardown(mut query: Query<(&Transform, &mut Phases<BoardMode>), With<Tile>>) {
-    for (transform, mut phases) in &mut query {
-        let offset = transform.translation.x + transform.translation.z;
+fn enter_teardown(
+    mut query: Query<(&Position, &mut Phases<BoardMode>), With<Tile>>,
+    board: Res<Board>,
+) {
+    for (position, mut phases) in &mut query {
         phases.reset(vec![
-            Phase::new(BoardMode::Waiting, 0.1 + offset as f32 * 0.05),
-            Phase::new(BoardMode::Exiting, 1.0),
+            Phase::new(BoardMode::Waiting, board.stagger(position.vec)),
+            Phase::new(BoardMode::Exiting, 1.0).with_easing(Easing::BackInOut),
             Phase::new(BoardMode::Hidden, 0.0), // final
         ]);
     }
 }
 
-#[derive(Resource, Default)]
+fn exit_teardown(mut commands: Commands, query: Query<Entity, With<Tile>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[derive(Resource, Default, Clone, Deserialize)]
+#[serde(try_from = "Vec<String>")]
 pub struct Board {
     pub tiles: HashSet<IVec2>,
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl Board {
+    pub fn size(&self) -> IVec2 {
+        self.max - self.min + IVec2::ONE
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max).as_vec2() / 2.0
+    }
+
+    pub fn stagger(&self, position: IVec2) -> f32 {
+        (position.x - self.min.x + self.max.y - position.y) as f32 * STAGGER
+    }
+
+    pub fn wave_duration(&self) -> f32 {
+        0.3 + self.stagger(IVec2::new(self.max.x, self.min.y)) + 1.0
+    }
+}
+
+// `#` is a tile and `.` or a space a hole, the middle of the rows at the origin
+impl TryFrom<Vec<String>> for Board {
+    type Error = String;
+
+    fn try_from(rows: Vec<String>) -> Result<Self, Self::Error> {
+        let height = rows.len() as i32;
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0) as i32;
+        let mut tiles = HashSet::new();
+        for (i, row) in rows.iter().enumerate() {
+            for (j, character) in row.chars().enumerate() {
+                match character {
+                    '#' => {
+                        let x = j as i32 - (width - 1) / 2;
+                        let y = (height - 1) / 2 - i as i32;
+                        tiles.insert(IVec2::new(x, y));
+                    }
+                    '.' | ' ' => (),
+                    _ => return Err(format!("unexpected board character `{}`", character)),
+                }
+            }
+        }
+        if !tiles.contains(&IVec2::ZERO) {
+            return Err("the board needs a tile at its center".to_string());
+        }
+        let min = tiles.iter().fold(IVec2::ZERO, |min, &tile| min.min(tile));
+        let max = tiles.iter().fold(IVec2::ZERO, |max, &tile| max.max(tile));
+        Ok(Self { tiles, min, max })
+    }
 }
 
 #[derive(Component)]
@@ -87,9 +155,17 @@ pub struct Position {
 }
 
 #[derive(Component)]
-pub struct Tile;
+pub struct Tile {
+    pub model: Entity,
+}
+
+impl Tile {
+    pub fn new(model: Entity) -> Self {
+        Self { model }
+    }
+}
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Deserialize)]
 pub enum BoardMode {
     Hidden,
     Entering,
@@ -98,36 +174,101 @@ pub enum BoardMode {
     Waiting,
 }
 
+impl Idle for BoardMode {
+    fn idle() -> Self {
+        BoardMode::Hidden
+    }
+}
+
 impl Position {
     pub fn new(vec: IVec2) -> Self {
         Self { vec }
     }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub struct Motion {
+    pub style: MotionStyle,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum MotionStyle {
+    Slide,
+    // height of the arc
+    Hop(f32),
+}
 
-    pub fn from_xy(x: i32, y: i32) -> Self {
+// travels between tiles while `Position` stays authoritative
+#[derive(Component)]
+pub struct Tween {
+    pub motion: Motion,
+    from: Vec2,
+    to: IVec2,
+    progress: f32,
+}
+
+impl Tween {
+    pub fn new(motion: Motion, position: IVec2) -> Self {
         Self {
-            vec: IVec2::new(x, y),
+            motion,
+            from: position.as_vec2(),
+            to: position,
+            progress: 1.0,
+        }
+    }
+
+    pub fn is_travelling(&self) -> bool {
+        self.progress < 1.0
+    }
+
+    pub fn height(&self) -> f32 {
+        match self.motion.style {
+            MotionStyle::Slide => 0.0,
+            MotionStyle::Hop(height) => height * 4.0 * self.progress * (1.0 - self.progress),
         }
     }
 }
 
-fn to_world_xz(mut query: Query<(&mut Transform, &Position)>) {
+fn to_world_xz(mut query: Query<(&mut Transform, &Position), Without<Tween>>) {
     for (mut transform, position) in &mut query {
         transform.translation.x = position.vec.x as f32;
         transform.translation.z = -position.vec.y as f32;
     }
 }
 
+pub fn tween(mut query: Query<(&mut Transform, &Position, &mut Tween)>, clocks: Res<Clocks>) {
+    for (mut transform, position, mut tween) in &mut query {
+        // a new target starts the travel from wherever the entity is
+        if position.vec != tween.to {
+            tween.from = Vec2::new(transform.translation.x, -transform.translation.z);
+            tween.to = position.vec;
+            tween.progress = 0.0;
+        }
+        let step = match tween.motion.duration > 0.0 {
+            true => clocks.get(Clock::Trial).delta().as_secs_f32() / tween.motion.duration,
+            false => 1.0,
+        };
+        tween.progress = f32::min(tween.progress + step, 1.0);
+        let eased = tween.motion.easing.ease(tween.progress);
+        let xy = tween.from.lerp(tween.to.as_vec2(), eased);
+        transform.translation.x = xy.x;
+        transform.translation.z = -xy.y;
+    }
+}
+
 fn to_world_y(mut query: Query<(&mut Transform, &Phases<BoardMode>)>) {
     for (mut transform, phases) in &mut query {
         let old_y = transform.translation.y;
         transform.translation.y = match phases.mode() {
             BoardMode::Hidden => HIDDEN_HEIGHT,
             BoardMode::Entering => {
-                HIDDEN_HEIGHT + (SHOWN_HEIGHT - HIDDEN_HEIGHT) * ease(phases.progress)
+                HIDDEN_HEIGHT + (SHOWN_HEIGHT - HIDDEN_HEIGHT) * phases.eased_progress()
             }
             BoardMode::Shown => SHOWN_HEIGHT,
             BoardMode::Exiting => {
-                let new_y = SHOWN_HEIGHT + (HIDDEN_HEIGHT - SHOWN_HEIGHT) * ease(phases.progress);
+                let new_y = SHOWN_HEIGHT + (HIDDEN_HEIGHT - SHOWN_HEIGHT) * phases.eased_progress();
                 let both_above = old_y >= SHOWN_HEIGHT && new_y >= SHOWN_HEIGHT;
                 let both_bellow = old_y <= HIDDEN_HEIGHT && new_y <= HIDDEN_HEIGHT;
                 match both_above || both_bellow {
@@ -139,12 +280,3 @@ fn to_world_y(mut query: Query<(&mut Transform, &Phases<BoardMode>)>) {
         };
     }
 }
-
-const C: f32 = 1.7;
-
-fn ease(x: f32) -> f32 {
-    match x < 0.5 {
-        true => ((2.0 * x).powi(2) * ((C + 1.0) * 2.0 * x - C)) / 2.0,
-        false => ((2.0 - 2.0 * x).powi(2) * ((C + 1.0) * (x * 2.0 - 2.0) + C) + 2.0) / 2.0,
-    }
-}
@@ -0,0 +1,297 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    background::{self, Seek},
+    board::{self, Board, Position, SHOWN_HEIGHT},
+    easing::Easing,
+    hazard::{self, Hazard},
+    laser::{visuals::ray_material, LaserMode},
+    material_from_color, palette,
+    phases::{self, Phase, PhaseEntered, Phases},
+    player,
+    trial::{BombDefinition, CurrentTrial, Trial},
+    AppState,
+};
+
+const BOMB_COLOR: Color = palette::DARK_RED;
+const FUSE_COLOR: Color = palette::LIGHT_WHITE;
+const DROP_HEIGHT: f32 = 8.0;
+// part of the fuse spent falling onto the board
+const LANDING: f32 = 0.3;
+
+pub struct BombPlugin;
+
+impl Plugin for BombPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Setup).with_system(enter_setup.after(board::enter_setup)),
+        )
+        .add_system_set(SystemSet::on_enter(AppState::Start).with_system(enter_start))
+        .add_system_set(
+            SystemSet::on_update(AppState::Game)
+                .with_system(
+                    seek.after(background::seek)
+                        .before(phases::transition::<LaserMode>),
+                )
+                .with_system(
+                    threaten
+                        .after(phases::transition::<LaserMode>)
+                        .after(player::movement)
+                        .before(hazard::hit),
+                ),
+        )
+        .add_system(fall.after(phases::transition::<LaserMode>))
+        .add_system(burn.after(phases::transition::<LaserMode>))
+        .add_system(sound.after(phases::transition::<LaserMode>))
+        .add_system_set(SystemSet::on_enter(AppState::Victory).with_system(disarm))
+        .add_system_set(SystemSet::on_enter(AppState::Teardown).with_system(disarm))
+        .add_system_set(SystemSet::on_exit(AppState::Teardown).with_system(exit_teardown));
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+pub enum Blast {
+    #[default]
+    Single,
+    Plus,
+    Square,
+}
+
+impl Blast {
+    fn cells(self, position: IVec2, board: &Board) -> Vec<IVec2> {
+        let offsets = match self {
+            Blast::Single => vec![IVec2::ZERO],
+            Blast::Plus => vec![IVec2::ZERO, IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y],
+            Blast::Square => (-1..=1)
+                .flat_map(|x| (-1..=1).map(move |y| IVec2::new(x, y)))
+                .collect(),
+        };
+        offsets
+            .into_iter()
+            .map(|offset| position + offset)
+            .filter(|cell| board.tiles.contains(cell))
+            .collect()
+    }
+}
+
+fn bomb(
+    definition: &BombDefinition,
+    board: &Board,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let fuse = commands
+        .spawn(MaterialMeshBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(0.06, 0.3, 0.06))),
+            material: materials.add(material_from_color(FUSE_COLOR)),
+            transform: Transform::from_xyz(0.0, 0.3, 0.0),
+            ..default()
+        })
+        .id();
+    let body = commands
+        .spawn(MaterialMeshBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere {
+                radius: 0.3,
+                ..default()
+            })),
+            material: materials.add(material_from_color(BOMB_COLOR)),
+            transform: Transform::from_xyz(0.0, DROP_HEIGHT, 0.0),
+            visibility: Visibility::INVISIBLE,
+            ..default()
+        })
+        .push_children(&[fuse])
+        .id();
+    let mesh = meshes.add(Mesh::from(shape::Box::new(0.9, 0.1, 0.9)));
+    let material = materials.add(ray_material());
+    let cells: Vec<Entity> = definition
+        .blast
+        .cells(definition.position, board)
+        .into_iter()
+        .map(|tile| {
+            let offset = tile - definition.position;
+            let cell = (
+                MaterialMeshBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(offset.x as f32, 0.05, -offset.y as f32),
+                    ..default()
+                },
+                NotShadowCaster,
+                NotShadowReceiver,
+            );
+            commands.spawn(cell).id()
+        })
+        .collect();
+    let explosion = commands
+        .spawn(SpatialBundle {
+            visibility: Visibility::INVISIBLE,
+            ..default()
+        })
+        .push_children(&cells)
+        .id();
+    let root = (
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, SHOWN_HEIGHT, 0.0)),
+        Position::new(definition.position),
+        Bomb::new(
+            definition.id.clone(),
+            definition.blast,
+            body,
+            fuse,
+            explosion,
+        ),
+        Hazard::new(),
+        Phases::new(LaserMode::Ready),
+    );
+    commands.spawn(root).push_children(&[body, explosion]);
+}
+
+fn enter_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    board: Res<Board>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+) {
+    let trial = trials.get(&current_trial.handle).unwrap();
+    for definition in &trial.bombs {
+        bomb(
+            definition,
+            &board,
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+        );
+    }
+}
+
+fn enter_start(
+    mut query: Query<(&Bomb, &mut Phases<LaserMode>)>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+) {
+    let trial = trials.get(&current_trial.handle).unwrap();
+    for (bomb, mut phases) in &mut query {
+        phases.reset(trial.timelines[&bomb.id].laser.clone());
+    }
+}
+
+fn disarm(mut query: Query<&mut Phases<LaserMode>, With<Bomb>>) {
+    for mut phases in &mut query {
+        phases.reset(vec![
+            Phase::new(LaserMode::Ready, 0.0), // final
+        ]);
+    }
+}
+
+fn exit_teardown(mut commands: Commands, query: Query<Entity, With<Bomb>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[derive(Component)]
+pub struct Bomb {
+    pub id: String,
+    pub blast: Blast,
+    body: Entity,
+    fuse: Entity,
+    explosion: Entity,
+}
+
+impl Bomb {
+    pub fn new(id: String, blast: Blast, body: Entity, fuse: Entity, explosion: Entity) -> Self {
+        Self {
+            id,
+            blast,
+            body,
+            fuse,
+            explosion,
+        }
+    }
+}
+
+fn seek(mut events: EventReader<Seek>, mut query: Query<&mut Phases<LaserMode>, With<Bomb>>) {
+    for event in events.iter() {
+        for mut phases in &mut query {
+            phases.seek(event.time);
+        }
+    }
+}
+
+fn threaten(
+    mut query: Query<(Entity, &Position, &Bomb, &Phases<LaserMode>, &mut Hazard)>,
+    mut events: EventReader<PhaseEntered<LaserMode>>,
+    board: Res<Board>,
+) {
+    let blasts = hazard::entered_attacks(&mut events);
+    for (entity, position, bomb, phases, mut hazard) in &mut query {
+        hazard.cells = bomb.blast.cells(position.vec, &board).into_iter().collect();
+        let blasting = matches!(phases.mode(), LaserMode::Attacking);
+        hazard.lethal = blasting || blasts.contains(&entity);
+        let burning = matches!(phases.mode(), LaserMode::Charging);
+        hazard.charge = burning.then_some(phases.progress);
+    }
+}
+
+fn fall(
+    bomb_query: Query<(&Bomb, &Phases<LaserMode>)>,
+    mut model_query: Query<(&mut Transform, &mut Visibility)>,
+) {
+    for (bomb, phases) in &bomb_query {
+        let (falling, blasting) = match phases.mode() {
+            LaserMode::Ready => (false, false),
+            LaserMode::Charging => (true, false),
+            LaserMode::Attacking => (false, true),
+        };
+        let (mut transform, mut visibility) = model_query.get_mut(bomb.body).unwrap();
+        visibility.is_visible = falling;
+        let landed = Easing::BounceOut.ease(phases.progress / LANDING);
+        transform.translation.y = match falling {
+            true => DROP_HEIGHT * (1.0 - landed) + 0.3,
+            false => DROP_HEIGHT,
+        };
+        let (_, mut visibility) = model_query.get_mut(bomb.explosion).unwrap();
+        visibility.is_visible = blasting;
+    }
+}
+
+fn burn(
+    bomb_query: Query<(&Bomb, &Phases<LaserMode>)>,
+    mut transform_query: Query<&mut Transform>,
+) {
+    for (bomb, phases) in &bomb_query {
+        let left = match phases.mode() {
+            LaserMode::Charging => 1.0 - phases.progress,
+            LaserMode::Ready | LaserMode::Attacking => 1.0,
+        };
+        let mut transform = transform_query.get_mut(bomb.fuse).unwrap();
+        transform.scale.y = left.max(0.01);
+        transform.translation.y = 0.3 * (0.5 + left / 2.0);
+    }
+}
+
+fn sound(
+    mut events: EventReader<PhaseEntered<LaserMode>>,
+    query: Query<(), With<Bomb>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+) {
+    for event in events.iter() {
+        if !query.contains(event.entity) {
+            continue;
+        }
+        let (path, volume) = match event.new {
+            LaserMode::Charging => ("sounds/charge.ogg", 0.1),
+            LaserMode::Attacking => ("sounds/attack.ogg", 0.2),
+            LaserMode::Ready => continue,
+        };
+        let sound = asset_server.load(path);
+        audio.play_with_settings(sound, PlaybackSettings::ONCE.with_volume(volume));
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    loader::{RonAsset, RonLoader},
+    player::{self, Player},
+    settings::Settings,
+    trial::{CurrentTrial, Trial},
+    AppState,
+};
+
+const CAMPAIGN_PATH: &str = "default.campaign.ron";
+#[cfg(not(target_arch = "wasm32"))]
+const PROGRESS_PATH: &str = "progress.ron";
+#[cfg(target_arch = "wasm32")]
+const PROGRESS_KEY: &str = "little-mad-trials.progress";
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<CampaignOrder>()
+            .init_asset_loader::<RonLoader<CampaignOrder>>()
+            .add_startup_system(setup)
+            .add_system_to_stage(CoreStage::PreUpdate, apply)
+            .add_system_set(SystemSet::on_enter(AppState::Victory).with_system(enter_victory))
+            .add_system_set(SystemSet::on_exit(AppState::Teardown).with_system(exit_teardown));
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CurrentCampaign::new(asset_server.load(CAMPAIGN_PATH)));
+    // no trial can start until the campaign is loaded
+    commands.insert_resource(CurrentTrial::new(0, Handle::default()));
+    let progress = load_progress().unwrap_or_default();
+    commands.insert_resource(Campaign::new(Vec::new(), progress));
+}
+
+#[derive(Resource)]
+struct CurrentCampaign {
+    handle: Handle<CampaignOrder>,
+}
+
+impl CurrentCampaign {
+    fn new(handle: Handle<CampaignOrder>) -> Self {
+        Self { handle }
+    }
+}
+
+#[derive(TypeUuid, Deserialize)]
+#[uuid = "3e8a5d21-7c4f-4b96-a0d2-9f61b8c7e504"]
+struct CampaignOrder {
+    // trials in the order they are played
+    trials: Vec<String>,
+}
+
+impl RonAsset for CampaignOrder {
+    type Data = Self;
+    const EXTENSIONS: &'static [&'static str] = &["campaign.ron"];
+
+    fn build(data: Self) -> Result<Self, String> {
+        match data.trials.is_empty() {
+            true => Err("a campaign needs at least one trial".to_string()),
+            false => Ok(data),
+        }
+    }
+}
+
+fn apply(
+    mut events: EventReader<AssetEvent<CampaignOrder>>,
+    mut campaign: ResMut<Campaign>,
+    mut current_trial: ResMut<CurrentTrial>,
+    current_campaign: Res<CurrentCampaign>,
+    orders: Res<Assets<CampaignOrder>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != current_campaign.handle {
+            continue;
+        }
+        let order = orders.get(handle).unwrap();
+        campaign.trials = order
+            .trials
+            .iter()
+            .map(|path| asset_server.load(path.as_str()))
+            .collect();
+        // the campaign might have lost trials since the progress was saved
+        let index = campaign.progress.trial.min(campaign.trials.len() - 1);
+        campaign.progress.trial = index;
+        *current_trial = CurrentTrial::new(index, campaign.trials[index].clone());
+    }
+}
+
+#[derive(Resource)]
+pub struct Campaign {
+    pub trials: Vec<Handle<Trial>>,
+    progress: Progress,
+}
+
+impl Campaign {
+    fn new(trials: Vec<Handle<Trial>>, progress: Progress) -> Self {
+        Self { trials, progress }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Progress {
+    // the last one staying once the campaign is over
+    trial: usize,
+}
+
+fn enter_victory(
+    mut campaign: ResMut<Campaign>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+    player_query: Query<&Player>,
+    settings: Res<Settings>,
+) {
+    // versus trials are played against each other, so neither a win nor a draw gets through them
+    if player::versus(&settings, player_query.iter().len()) {
+        return;
+    }
+    let next = current_trial.index + 1;
+    // a trial that failed to load is never reached
+    let Some(handle) = campaign.trials.get(next) else {
+        return;
+    };
+    if trials.contains(handle) && campaign.progress.trial < next {
+        campaign.progress.trial = next;
+        save_progress(&campaign.progress);
+    }
+}
+
+fn exit_teardown(mut current_trial: ResMut<CurrentTrial>, campaign: Res<Campaign>) {
+    let index = campaign.progress.trial;
+    if current_trial.index != index {
+        *current_trial = CurrentTrial::new(index, campaign.trials[index].clone());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_progress() -> Option<Progress> {
+    let text = std::fs::read_to_string(PROGRESS_PATH).ok()?;
+    ron::from_str(&text).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_progress(progress: &Progress) {
+    let result = ron::to_string(progress)
+        .map_err(|error| error.to_string())
+        .and_then(|text| std::fs::write(PROGRESS_PATH, text).map_err(|error| error.to_string()));
+    if let Err(error) = result {
+        warn!("failed to save the progress: {}", error);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_progress() -> Option<Progress> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    let text = storage.get_item(PROGRESS_KEY).ok()??;
+    ron::from_str(&text).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_progress(progress: &Progress) {
+    let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+    let (Some(storage), Ok(text)) = (storage, ron::to_string(progress)) else {
+        warn!("failed to save the progress");
+        return;
+    };
+    if storage.set_item(PROGRESS_KEY, &text).is_err() {
+        warn!("failed to save the progress");
+    }
+}
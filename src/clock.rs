@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Clocks::new())
+            .add_system_to_stage(CoreStage::PreUpdate, tick)
+            .add_system(scale);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Clock {
+    Trial,
+    Interface,
+    Background,
+}
+
+#[derive(Resource)]
+pub struct Clocks {
+    map: HashMap<Clock, ClockState>,
+}
+
+impl Clocks {
+    pub fn new() -> Self {
+        let clocks = [Clock::Trial, Clock::Interface, Clock::Background];
+        Self {
+            map: clocks
+                .map(|clock| (clock, ClockState::new()))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, clock: Clock) -> &ClockState {
+        &self.map[&clock]
+    }
+
+    pub fn get_mut(&mut self, clock: Clock) -> &mut ClockState {
+        self.map.get_mut(&clock).unwrap()
+    }
+}
+
+pub struct ClockState {
+    pub scale: f32,
+    pub paused: bool,
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl ClockState {
+    pub fn new() -> Self {
+        Self {
+            scale: 1.0,
+            paused: false,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+fn tick(mut clocks: ResMut<Clocks>, time: Res<Time>) {
+    for clock in clocks.map.values_mut() {
+        clock.delta = match clock.paused {
+            true => Duration::ZERO,
+            false => time.delta().mul_f32(clock.scale),
+        };
+        clock.elapsed += clock.delta;
+    }
+}
+
+// debug builds can halve or double the speed of the trial with [[] and []]
+fn scale(input: Res<Input<KeyCode>>, mut clocks: ResMut<Clocks>) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let clock = clocks.get_mut(Clock::Trial);
+    if input.just_pressed(KeyCode::LBracket) {
+        clock.scale = f32::max(clock.scale / 2.0, 0.125);
+    }
+    if input.just_pressed(KeyCode::RBracket) {
+        clock.scale = f32::min(clock.scale * 2.0, 2.0);
+    }
+}
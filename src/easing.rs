@@ -0,0 +1,100 @@
+use std::f32::consts::PI;
+
+use serde::Deserialize;
+
+// overshoot of the back curves
+const BACK: f32 = 1.7;
+// period of the elastic curves, as a fraction of the transition
+const ELASTIC: f32 = 0.3;
+
+// back and elastic curves overshoot outside of `0..=1`
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    pub fn ease(self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => x,
+            Easing::QuadIn => quad(x),
+            Easing::QuadOut => out(quad, x),
+            Easing::QuadInOut => in_out(quad, x),
+            Easing::CubicIn => cubic(x),
+            Easing::CubicOut => out(cubic, x),
+            Easing::CubicInOut => in_out(cubic, x),
+            Easing::BackIn => back(x),
+            Easing::BackOut => out(back, x),
+            Easing::BackInOut => in_out(back, x),
+            Easing::ElasticIn => elastic(x),
+            Easing::ElasticOut => out(elastic, x),
+            Easing::ElasticInOut => in_out(elastic, x),
+            Easing::BounceIn => out(bounce, x),
+            Easing::BounceOut => bounce(x),
+            Easing::BounceInOut => in_out(|x| out(bounce, x), x),
+        }
+    }
+}
+
+fn out(ease_in: impl Fn(f32) -> f32, x: f32) -> f32 {
+    1.0 - ease_in(1.0 - x)
+}
+
+fn in_out(ease_in: impl Fn(f32) -> f32, x: f32) -> f32 {
+    match x < 0.5 {
+        true => ease_in(2.0 * x) / 2.0,
+        false => 1.0 - ease_in(2.0 - 2.0 * x) / 2.0,
+    }
+}
+
+fn quad(x: f32) -> f32 {
+    x * x
+}
+
+fn cubic(x: f32) -> f32 {
+    x * x * x
+}
+
+fn back(x: f32) -> f32 {
+    x * x * ((BACK + 1.0) * x - BACK)
+}
+
+fn elastic(x: f32) -> f32 {
+    match x {
+        x if x <= 0.0 => 0.0,
+        x if x >= 1.0 => 1.0,
+        x => {
+            -(2.0_f32.powf(10.0 * x - 10.0))
+                * ((x - 1.0 - ELASTIC / 4.0) * 2.0 * PI / ELASTIC).sin()
+        }
+    }
+}
+
+// bounces are defined easing out, as they land on the target value
+fn bounce(x: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    match x {
+        x if x < 1.0 / D => N * x * x,
+        x if x < 2.0 / D => N * (x - 1.5 / D).powi(2) + 0.75,
+        x if x < 2.5 / D => N * (x - 2.25 / D).powi(2) + 0.9375,
+        x => N * (x - 2.625 / D).powi(2) + 0.984375,
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    board::{Position, Tile},
+    laser::LaserMode,
+    palette,
+    phases::PhaseEntered,
+    player::{self, Damage, Player},
+    settings::Settings,
+    AppState,
+};
+
+const WARNING_COLOR: Color = palette::DARK_RED;
+const WARNING_BRIGHTNESS: f32 = 0.8;
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::Game).with_system(hit.after(player::movement)),
+        )
+        .add_system(telegraph.after(hit));
+    }
+}
+
+// kept up to date by the systems of each hazard before `hit` runs
+#[derive(Component, Default)]
+pub struct Hazard {
+    pub cells: HashSet<IVec2>,
+    pub lethal: bool,
+    pub charge: Option<f32>,
+}
+
+impl Hazard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// attacks entered this frame are lethal even if a long frame already skipped them
+pub fn entered_attacks(events: &mut EventReader<PhaseEntered<LaserMode>>) -> HashSet<Entity> {
+    events
+        .iter()
+        .filter(|event| matches!(event.new, LaserMode::Attacking))
+        .map(|event| event.entity)
+        .collect()
+}
+
+pub fn hit(
+    hazard_query: Query<(Entity, &Hazard)>,
+    player_query: Query<(Entity, &Position), With<Player>>,
+    mut damage_events: EventWriter<Damage>,
+) {
+    for (entity, hazard) in &hazard_query {
+        if !hazard.lethal {
+            continue;
+        }
+        // knocked out players ignore the damage
+        for (player, position) in &player_query {
+            if hazard.cells.contains(&position.vec) {
+                damage_events.send(Damage::new(player, entity, 1));
+            }
+        }
+    }
+}
+
+fn telegraph(
+    hazard_query: Query<&Hazard>,
+    tile_query: Query<(&Position, &Tile)>,
+    material_query: Query<&Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+    state: Res<State<AppState>>,
+) {
+    let mut warnings: HashMap<IVec2, f32> = HashMap::new();
+    // hazards keep their last charge once the trial is over
+    let playing = matches!(state.current(), AppState::Game | AppState::Paused);
+    if settings.telegraph && playing {
+        for hazard in &hazard_query {
+            let Some(charge) = hazard.charge else {
+                continue;
+            };
+            for &cell in &hazard.cells {
+                let warning = warnings.entry(cell).or_insert(0.0);
+                *warning = warning.max(charge);
+            }
+        }
+    }
+    for (position, tile) in &tile_query {
+        let warning = warnings.get(&position.vec).copied().unwrap_or(0.0);
+        let emissive = WARNING_COLOR * WARNING_BRIGHTNESS * warning;
+        let handle = material_query.get(tile.model).unwrap();
+        // avoids preparing the material again every frame
+        if materials.get(handle).unwrap().emissive != emissive {
+            materials.get_mut(handle).unwrap().emissive = emissive;
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::{audio::AudioSink, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    background::{self, Countdown},
+    trial::{CurrentTrial, Trial},
+    AppState, HIGH_CHROMATIC_ABERRATION, LOW_CHROMATIC_ABERRATION, MEDIUM_CHROMATIC_ABERRATION,
+};
+
+const MUSIC_VOLUME: f32 = 0.3;
+// share of the music volume kept while paused
+const PAUSED_MUSIC: f32 = 0.3;
+
+pub struct IntensityPlugin;
+
+impl Plugin for IntensityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Intensity>()
+            .init_resource::<Music>()
+            .init_resource::<PausedSounds>()
+            .add_system_set(SystemSet::on_enter(AppState::Start).with_system(enter_start))
+            .add_system_set(
+                SystemSet::on_update(AppState::Game)
+                    .with_system(intensify.after(background::seek))
+                    .with_system(mix.after(intensify)),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(duck))
+            .add_system_set(SystemSet::on_resume(AppState::Start).with_system(unduck))
+            .add_system_set(SystemSet::on_resume(AppState::Game).with_system(unduck))
+            .add_system_set(SystemSet::on_update(AppState::Start).with_system(resume))
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(resume))
+            .add_system_set(SystemSet::on_enter(AppState::Teardown).with_system(enter_teardown));
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Stage {
+    pub time: f32,
+    pub fast: bool,
+    pub aberration: Aberration,
+    // lasers attacking at once needed for the aberration
+    pub crowd: usize,
+    pub music: usize,
+}
+
+impl Default for Stage {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            fast: false,
+            aberration: Aberration::Medium,
+            crowd: 1,
+            music: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum Aberration {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Aberration {
+    pub fn value(self) -> f32 {
+        match self {
+            Aberration::Low => LOW_CHROMATIC_ABERRATION,
+            Aberration::Medium => MEDIUM_CHROMATIC_ABERRATION,
+            Aberration::High => HIGH_CHROMATIC_ABERRATION,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Intensity {
+    pub stage: Stage,
+}
+
+#[derive(Resource, Default)]
+struct Music {
+    layers: Vec<Handle<AudioSink>>,
+}
+
+#[derive(Resource, Default)]
+struct PausedSounds {
+    sinks: Vec<Handle<AudioSink>>,
+}
+
+fn enter_start(
+    mut intensity: ResMut<Intensity>,
+    mut music: ResMut<Music>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let trial = trials.get(&current_trial.handle).unwrap();
+    *intensity = Intensity::default();
+    // layers start muted, so that they stay in sync when brought in
+    music.layers = trial
+        .music
+        .iter()
+        .map(|path| {
+            let settings = PlaybackSettings::LOOP.with_volume(0.0);
+            let sink = audio.play_with_settings(asset_server.load(path.as_str()), settings);
+            audio_sinks.get_handle(sink)
+        })
+        .collect();
+}
+
+fn enter_teardown(
+    mut music: ResMut<Music>,
+    mut paused_sounds: ResMut<PausedSounds>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let paused = paused_sounds.sinks.drain(..);
+    for handle in music.layers.drain(..).chain(paused) {
+        if let Some(sink) = audio_sinks.get(&handle) {
+            sink.stop();
+        }
+    }
+}
+
+pub fn intensify(
+    mut intensity: ResMut<Intensity>,
+    countdown: Res<Countdown>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+) {
+    let trial = trials.get(&current_trial.handle).unwrap();
+    let elapsed = countdown.timer.elapsed_secs();
+    // the loader guarantees that the stages are in order
+    let stage = trial
+        .stages
+        .iter()
+        .rev()
+        .find(|stage| stage.time <= elapsed);
+    intensity.stage = stage.cloned().unwrap_or_default();
+}
+
+fn mix(music: Res<Music>, intensity: Res<Intensity>, audio_sinks: Res<Assets<AudioSink>>) {
+    for (i, layer) in music.layers.iter().enumerate() {
+        // sinks only exist once their sound has loaded
+        if let Some(sink) = audio_sinks.get(layer) {
+            let volume = match i < intensity.stage.music {
+                true => MUSIC_VOLUME,
+                false => 0.0,
+            };
+            sink.set_volume(volume);
+        }
+    }
+}
+
+fn duck(
+    music: Res<Music>,
+    mut paused_sounds: ResMut<PausedSounds>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    for (id, sink) in audio_sinks.iter() {
+        match music.layers.iter().any(|layer| layer.id() == id) {
+            true => sink.set_volume(sink.volume() * PAUSED_MUSIC),
+            false => {
+                sink.pause();
+                paused_sounds.sinks.push(audio_sinks.get_handle(id));
+            }
+        }
+    }
+}
+
+// restores the volumes itself, as `mix` only runs in `Game` and not in `Start`
+fn unduck(music: Res<Music>, audio_sinks: Res<Assets<AudioSink>>) {
+    for layer in &music.layers {
+        if let Some(sink) = audio_sinks.get(layer) {
+            sink.set_volume(sink.volume() / PAUSED_MUSIC);
+        }
+    }
+}
+
+// replacing the pause page resumes the trial without updating it before the teardown,
+// which stops the paused sounds instead
+fn resume(mut paused_sounds: ResMut<PausedSounds>, audio_sinks: Res<Assets<AudioSink>>) {
+    for handle in paused_sounds.sinks.drain(..) {
+        if let Some(sink) = audio_sinks.get(&handle) {
+            sink.play();
+        }
+    }
+}
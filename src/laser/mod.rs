@@ -1,186 +1,182 @@
-mod content;
-mod visuals;
+mod tracking;
+pub mod visuals;
 
 use std::f32::consts::PI;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+use serde::Deserialize;
 
 use crate::{
-    background::{self, Countdown},
-    board::{BoardMode, Position, HIDDEN_HEIGHT},
+    background::{self, Seek},
+    board::{self, Board, BoardMode, Position, HIDDEN_HEIGHT},
+    easing::Easing,
+    hazard::{self, Hazard},
+    intensity::{self, Intensity},
     palette,
-    phases::{self, Phase, Phases},
+    phases::{self, Phase, PhaseEntered, Phases, PhasesPlugin},
     player::{self, Health, Player},
+    timeline::Idle,
+    trial::{CurrentTrial, LaserDefinition, Trial},
     AppState,
 };
 
 use self::{
-    content::{
-        lower_laser_board_phases, lower_laser_phases, middle_laser_board_phases,
-        middle_laser_phases, mobile_laser_board_phases, mobile_laser_phases,
-        upper_laser_board_phases, upper_laser_phases,
-    },
+    tracking::{movement, Tracker},
     visuals::{ray_blueprint, turrets_blueprint, Visuals, VisualsPlugin},
 };
 
+pub use self::tracking::Tracking;
+
+// radians between the orientations checked along a sweep
+const SWEEP_STEP: f32 = 0.05;
+
 pub struct LaserPlugin;
 
 impl Plugin for LaserPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(VisualsPlugin)
-            .add_startup_system(setup)
-            .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(enter_setup))
+        app.add_plugin(PhasesPlugin::<LaserMode>::default())
+            .add_plugin(VisualsPlugin)
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(enter_setup.after(board::enter_setup)),
+            )
             .add_system_set(SystemSet::on_enter(AppState::Start).with_system(enter_start))
             .add_system_set(
                 SystemSet::on_update(AppState::Game)
-                    .with_system(movement.after(phases::transition::<LaserMode>))
-                    .with_system(attack.after(movement).after(player::movement))
-                    .with_system(fast_mode.after(background::countdown)),
+                    .with_system(
+                        seek.after(background::seek)
+                            .before(phases::transition::<BoardMode>)
+                            .before(phases::transition::<LaserMode>),
+                    )
+                    .with_system(target)
+                    .with_system(
+                        movement
+                            .after(phases::transition::<LaserMode>)
+                            .after(target),
+                    )
+                    .with_system(
+                        threaten
+                            .after(movement)
+                            .after(player::movement)
+                            .before(hazard::hit),
+                    )
+                    .with_system(fast_mode.after(intensity::intensify)),
             )
             .add_system_set(SystemSet::on_enter(AppState::Victory).with_system(enter_victory))
             .add_system_set(SystemSet::on_enter(AppState::Teardown).with_system(enter_teardown))
-            .add_system(phases::transition::<LaserMode>.after(background::countdown));
+            .add_system_set(SystemSet::on_exit(AppState::Teardown).with_system(exit_teardown));
     }
 }
 
-fn setup(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    laser(
-        IVec2::ZERO,
-        Axis::Vertical,
-        true,
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-    );
-    laser(
-        IVec2::new(0, 1),
-        Axis::Horizontal,
-        false,
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-    );
-    laser(
-        IVec2::new(0, 0),
-        Axis::Horizontal,
-        false,
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-    );
-    laser(
-        IVec2::new(0, -1),
-        Axis::Horizontal,
-        false,
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-    );
-}
-
 fn laser(
-    position: IVec2,
-    axis: Axis,
-    mobile: bool,
+    definition: &LaserDefinition,
+    board: &Board,
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
 ) {
-    let rotation = match axis {
-        Axis::Horizontal => PI / 2.0,
-        Axis::Vertical => 0.0,
+    let rotation = Quat::from_rotation_y(definition.axis.angle());
+    let (offset, reach) = match (definition.sweep, definition.axis) {
+        // sweeping lasers turn around their position, reaching the farthest corner
+        (Some(_), _) => {
+            let corners = [
+                board.min,
+                board.max,
+                IVec2::new(board.min.x, board.max.y),
+                IVec2::new(board.max.x, board.min.y),
+            ];
+            let farthest = corners
+                .into_iter()
+                .map(|corner| (corner - definition.position).as_vec2().length())
+                .fold(0.0, f32::max);
+            (0.0, farthest + 0.5)
+        }
+        // other lasers are centered on the board along their line
+        (None, axis) => {
+            let center = board.center() - definition.position.as_vec2();
+            let offset = center.dot(axis.direction());
+            let length = match axis {
+                Axis::Horizontal => board.size().x as f32,
+                Axis::Vertical => board.size().y as f32,
+                Axis::Diagonal | Axis::AntiDiagonal => board.size().as_vec2().length(),
+            };
+            (offset, length / 2.0 + 0.5)
+        }
     };
-    let normal = turrets_blueprint(mobile, commands, palette::DARK_YELLOW, meshes, materials);
-    let fast = turrets_blueprint(
-        mobile,
-        commands,
-        Color::rgb_u8(252, 142, 73),
-        meshes,
-        materials,
-    );
-    let charging = turrets_blueprint(mobile, commands, palette::DARK_RED, meshes, materials);
-    let ray = ray_blueprint(commands, meshes, materials);
-    let model = SpatialBundle::from_transform(
-        Transform::from_xyz(0.0, 0.3, 0.0).with_rotation(Quat::from_rotation_y(rotation)),
-    );
+    let mobile = definition.mobile;
+    let colors = &definition.colors;
+    let normal = turrets_blueprint(mobile, reach, commands, colors.normal, meshes, materials);
+    let fast = turrets_blueprint(mobile, reach, commands, colors.fast, meshes, materials);
+    let charging = turrets_blueprint(mobile, reach, commands, colors.charging, meshes, materials);
+    let ray = ray_blueprint(reach, commands, meshes, materials);
+    let model = commands
+        .spawn(SpatialBundle::from_transform(
+            Transform::from_translation(Vec3::Y * 0.3 + rotation * Vec3::Z * offset)
+                .with_rotation(rotation),
+        ))
+        .push_children(&[normal, fast, charging, ray])
+        .id();
+    let mut board_phases = Phases::new(BoardMode::Hidden);
+    if mobile {
+        board_phases.reset(vec![
+            Phase::new(BoardMode::Waiting, 0.3 + board.stagger(definition.position)),
+            Phase::new(BoardMode::Entering, 1.0).with_easing(Easing::BackInOut),
+            Phase::new(BoardMode::Shown, 0.0), // final
+        ]);
+    }
     let root = (
         SpatialBundle::from_transform(Transform::from_xyz(0.0, HIDDEN_HEIGHT, 0.0)),
-        Position::new(position),
-        Laser::new(axis, mobile),
-        Visuals::new(normal, fast, charging, ray),
-        Phases::new(BoardMode::Hidden),
+        Position::new(definition.position),
+        Laser::new(
+            definition.id.clone(),
+            definition.position,
+            definition.axis,
+            definition.sweep,
+            mobile,
+        ),
+        Visuals::new(model, normal, fast, charging, ray),
+        Hazard::new(),
+        board_phases,
         Phases::new(LaserMode::Ready),
     );
-    commands.spawn(root).with_children(|builder| {
-        builder
-            .spawn(model)
-            .push_children(&[normal, fast, charging, ray]);
-    });
+    let mut entity = commands.spawn(root);
+    entity.push_children(&[model]);
+    if mobile {
+        entity.insert(Tracker::new(definition.tracking));
+    }
 }
 
 fn enter_setup(
-    mut query: Query<(
-        &mut Position,
-        &mut Laser,
-        &mut Phases<BoardMode>,
-        &mut Phases<LaserMode>,
-    )>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    board: Res<Board>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
 ) {
-    for (mut position, mut laser, mut board_phases, mut laser_phases) in &mut query {
-        if laser.mobile {
-            position.vec = IVec2::ZERO;
-        };
-        laser.fast = false;
-        let vec = match laser.mobile {
-            true => vec![
-                Phase::new(BoardMode::Waiting, 0.4),  // 0.4
-                Phase::new(BoardMode::Entering, 1.0), // 1.4
-                Phase::new(BoardMode::Shown, 0.0),    // final
-            ],
-            false => vec![
-                Phase::new(BoardMode::Hidden, 0.0), // final
-            ],
-        };
-        board_phases.reset(vec);
-        laser_phases.reset(vec![
-            Phase::new(LaserMode::Ready, 0.0), // final
-        ]);
+    let trial = trials.get(&current_trial.handle).unwrap();
+    for definition in &trial.lasers {
+        laser(
+            definition,
+            &board,
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+        );
     }
 }
 
 fn enter_start(
-    mut query: Query<(
-        &Position,
-        &Laser,
-        &mut Phases<BoardMode>,
-        &mut Phases<LaserMode>,
-    )>,
+    mut query: Query<(&Laser, &mut Phases<BoardMode>, &mut Phases<LaserMode>)>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
 ) {
-    for (position, laser, mut board_phases, mut laser_phases) in &mut query {
-        let vec = match laser.mobile {
-            true => mobile_laser_board_phases(),
-            false => match position.vec.y {
-                1 => upper_laser_board_phases(),
-                0 => middle_laser_board_phases(),
-                -1 => lower_laser_board_phases(),
-                _ => unreachable!(),
-            },
-        };
-        board_phases.reset(vec);
-        let vec = match laser.mobile {
-            true => mobile_laser_phases(),
-            false => match position.vec.y {
-                1 => upper_laser_phases(),
-                0 => middle_laser_phases(),
-                -1 => lower_laser_phases(),
-                _ => unreachable!(),
-            },
-        };
-        laser_phases.reset(vec);
+    let trial = trials.get(&current_trial.handle).unwrap();
+    for (laser, mut board_phases, mut laser_phases) in &mut query {
+        // the loader guarantees that every laser has its timelines
+        let timelines = &trial.timelines[&laser.id];
+        board_phases.reset(timelines.board.clone());
+        laser_phases.reset(timelines.laser.clone());
     }
 }
 
@@ -194,10 +190,10 @@ fn enter_victory(
 ) {
     for (mut position, laser, mut board_phases, mut laser_phases) in &mut query {
         if laser.mobile {
-            position.vec.x = 0;
+            position.vec = laser.origin;
         }
         board_phases.reset(vec![
-            Phase::new(BoardMode::Exiting, 1.0),
+            Phase::new(BoardMode::Exiting, 1.0).with_easing(Easing::BackInOut),
             Phase::new(BoardMode::Hidden, 0.0), // final
         ]);
         laser_phases.reset(vec![
@@ -210,7 +206,7 @@ fn enter_teardown(mut query: Query<(&mut Phases<BoardMode>, &mut Phases<LaserMod
     for (mut board_phases, mut laser_phases) in &mut query {
         board_phases.reset(vec![
             Phase::new(BoardMode::Waiting, 0.1), // 0.1
-            Phase::new(BoardMode::Exiting, 1.0), // 1.1
+            Phase::new(BoardMode::Exiting, 1.0).with_easing(Easing::BackInOut), // 1.1
             Phase::new(BoardMode::Hidden, 0.0),  // final
         ]);
         laser_phases.reset(vec![
@@ -219,69 +215,203 @@ fn enter_teardown(mut query: Query<(&mut Phases<BoardMode>, &mut Phases<LaserMod
     }
 }
 
+fn exit_teardown(mut commands: Commands, query: Query<Entity, With<Laser>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 #[derive(Component)]
 pub struct Laser {
+    pub id: String,
+    pub origin: IVec2,
     pub axis: Axis,
+    pub sweep: Option<Axis>,
+    pub angle: f32,
     pub mobile: bool,
     pub fast: bool,
+    pub target: Option<Entity>,
 }
 
 impl Laser {
-    pub fn new(axis: Axis, mobile: bool) -> Self {
+    pub fn new(id: String, origin: IVec2, axis: Axis, sweep: Option<Axis>, mobile: bool) -> Self {
         Self {
+            id,
+            origin,
             axis,
+            sweep,
+            angle: axis.angle(),
             mobile,
             fast: false,
+            target: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct LaserColors {
+    pub normal: Color,
+    pub fast: Color,
+    pub charging: Color,
+}
+
+impl Default for LaserColors {
+    fn default() -> Self {
+        Self {
+            normal: palette::DARK_YELLOW,
+            fast: Color::rgb_u8(252, 142, 73),
+            charging: palette::DARK_RED,
         }
     }
 }
 
+#[derive(Clone, Copy, Deserialize)]
 pub enum Axis {
-    Horizontal,
     Vertical,
+    // falling to the right, through the cells where `x + y` is the same
+    AntiDiagonal,
+    Horizontal,
+    // rising to the right, through the cells where `x - y` is the same
+    Diagonal,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl Axis {
+    // lays a model built along the z axis on the line
+    pub fn angle(self) -> f32 {
+        match self {
+            Axis::Vertical => 0.0,
+            Axis::AntiDiagonal => PI / 4.0,
+            Axis::Horizontal => PI / 2.0,
+            Axis::Diagonal => 3.0 * PI / 4.0,
+        }
+    }
+
+    pub fn direction(self) -> Vec2 {
+        direction(self.angle())
+    }
+}
+
+fn direction(angle: f32) -> Vec2 {
+    // the board y axis points away from the camera, towards negative z
+    Vec2::new(angle.sin(), -angle.cos())
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
 pub enum LaserMode {
     Ready,
     Charging,
     Attacking,
 }
 
-fn movement(
-    mut laser_query: Query<(&mut Position, &Laser, &Phases<LaserMode>), Without<Player>>,
-    player_query: Query<&Position, With<Player>>,
+impl Idle for LaserMode {
+    fn idle() -> Self {
+        LaserMode::Ready
+    }
+}
+
+fn seek(
+    mut events: EventReader<Seek>,
+    mut query: Query<(&mut Phases<BoardMode>, &mut Phases<LaserMode>), With<Laser>>,
 ) {
-    let player_position = player_query.single().vec;
-    for (mut laser_position, laser, phases) in &mut laser_query {
-        if laser.mobile && matches!(phases.mode(), LaserMode::Ready) {
-            match laser.axis {
-                Axis::Horizontal => laser_position.vec.y = player_position.y,
-                Axis::Vertical => laser_position.vec.x = player_position.x,
-            }
+    for event in events.iter() {
+        for (mut board_phases, mut laser_phases) in &mut query {
+            board_phases.seek(event.time);
+            laser_phases.seek(event.time);
         }
     }
 }
 
-pub fn attack(
-    laser_query: Query<(&Position, &Laser, &Phases<LaserMode>), Without<Player>>,
-    mut player_query: Query<&Position, With<Player>>,
-    mut health: ResMut<Health>,
+fn target(
+    mut laser_query: Query<&mut Laser>,
+    player_query: Query<(Entity, &Health), With<Player>>,
 ) {
-    let player_position = player_query.single_mut();
-    for (laser_position, laser, phases) in &laser_query {
-        let aligned = match laser.axis {
-            Axis::Horizontal => laser_position.vec.y == player_position.vec.y,
-            Axis::Vertical => laser_position.vec.x == player_position.vec.x,
+    let mut targeted: Vec<(Entity, usize)> = player_query
+        .iter()
+        .filter(|(_, health)| health.points > 0)
+        .map(|(player, _)| (player, 0))
+        .collect();
+    for laser in &laser_query {
+        if let Some((_, count)) = targeted
+            .iter_mut()
+            .find(|(player, _)| Some(*player) == laser.target)
+        {
+            *count += 1;
+        }
+    }
+    for mut laser in &mut laser_query {
+        let standing = targeted
+            .iter()
+            .any(|&(player, _)| Some(player) == laser.target);
+        if !laser.mobile || standing {
+            continue;
+        }
+        // the least followed player, the first one on ties
+        let least = targeted.iter_mut().min_by_key(|(_, count)| *count);
+        laser.target = least.map(|(player, count)| {
+            *count += 1;
+            *player
+        });
+    }
+}
+
+fn threaten(
+    mut query: Query<(
+        Entity,
+        &Position,
+        &mut Laser,
+        &Phases<LaserMode>,
+        &mut Hazard,
+    )>,
+    mut events: EventReader<PhaseEntered<LaserMode>>,
+    board: Res<Board>,
+) {
+    let attackers = hazard::entered_attacks(&mut events);
+    for (entity, position, mut laser, phases, mut hazard) in &mut query {
+        let attacking = matches!(phases.mode(), LaserMode::Attacking);
+        let start = laser.axis.angle();
+        let end = laser.sweep.map_or(start, Axis::angle);
+        let angle = match attacking {
+            true => start + (end - start) * phases.eased_progress(),
+            false => start,
         };
-        if matches!(phases.mode(), LaserMode::Attacking) && aligned {
-            health.dead = true;
+        // attacks cover what they swept since the last frame, the threat is the whole sweep
+        let (from, to) = match attacking {
+            true => (laser.angle, angle),
+            false => (start, end),
+        };
+        laser.angle = angle;
+        hazard.cells = crossed(position.vec, from, to, &board);
+        hazard.lethal = attacking || attackers.contains(&entity);
+        let charging = matches!(phases.mode(), LaserMode::Charging);
+        hazard.charge = charging.then_some(phases.progress);
+    }
+}
+
+fn crossed(position: IVec2, from: f32, to: f32, board: &Board) -> HashSet<IVec2> {
+    let steps = ((to - from).abs() / SWEEP_STEP).ceil().max(1.0) as i32;
+    let directions: Vec<Vec2> = (0..=steps)
+        .map(|i| direction(from + (to - from) * i as f32 / steps as f32))
+        .collect();
+    let mut cells = HashSet::new();
+    for x in board.min.x..=board.max.x {
+        for y in board.min.y..=board.max.y {
+            let cell = IVec2::new(x, y);
+            let offset = (cell - position).as_vec2();
+            // lines crossing near the center of a cell, leaving out the corners they graze
+            if directions
+                .iter()
+                .any(|&direction| offset.perp_dot(direction).abs() < 0.5)
+            {
+                cells.insert(cell);
+            }
         }
     }
+    cells
 }
 
-pub fn fast_mode(mut query: Query<&mut Laser>, countdown: Res<Countdown>) {
+pub fn fast_mode(mut query: Query<&mut Laser>, intensity: Res<Intensity>) {
     for mut laser in &mut query {
-        laser.fast = (8.1..20.0).contains(&countdown.timer.elapsed_secs());
+        laser.fast = intensity.stage.fast;
     }
 }
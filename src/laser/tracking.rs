@@ -0,0 +1,134 @@
+use bevy::{prelude::*, utils::HashSet};
+use serde::Deserialize;
+
+use crate::{
+    board::{Board, Position, Tween},
+    clock::{Clock, Clocks},
+    phases::{PhaseEntered, Phases},
+    player::{Moves, Player},
+};
+
+use super::{Axis, Laser, LaserMode};
+
+#[derive(Clone, Copy, Default, Deserialize)]
+pub enum Tracking {
+    #[default]
+    Snap,
+    // tiles per second
+    Follow(f32),
+    Predict,
+    Random,
+    // tiles per second, ignoring players
+    Sweep(f32),
+}
+
+#[derive(Component)]
+pub struct Tracker {
+    tracking: Tracking,
+    // tiles the laser can still travel before its next step
+    travel: f32,
+    // direction of a sweep along the lines
+    sweep_direction: i32,
+}
+
+impl Tracker {
+    pub fn new(tracking: Tracking) -> Self {
+        Self {
+            tracking,
+            travel: 0.0,
+            sweep_direction: 1,
+        }
+    }
+
+    fn follow(&mut self, line: i32, target: i32, distance: f32) -> i32 {
+        if line == target {
+            // no travel is saved up while waiting on the target
+            self.travel = 0.0;
+            return line;
+        }
+        self.travel += distance;
+        let steps = (self.travel.floor() as i32).min((target - line).abs());
+        self.travel -= steps as f32;
+        line + steps * (target - line).signum()
+    }
+
+    fn sweep(&mut self, line: i32, min: i32, max: i32, distance: f32) -> i32 {
+        if min == max {
+            return line;
+        }
+        self.travel += distance;
+        let mut line = line;
+        while self.travel >= 1.0 {
+            if !(min..=max).contains(&(line + self.sweep_direction)) {
+                self.sweep_direction = -self.sweep_direction;
+            }
+            line += self.sweep_direction;
+            self.travel -= 1.0;
+        }
+        line
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn movement(
+    mut laser_query: Query<
+        (
+            Entity,
+            &mut Position,
+            &Laser,
+            &mut Tracker,
+            &Phases<LaserMode>,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<(&Position, &Moves, &Tween), With<Player>>,
+    mut events: EventReader<PhaseEntered<LaserMode>>,
+    board: Res<Board>,
+    clocks: Res<Clocks>,
+) {
+    let readied: HashSet<Entity> = events
+        .iter()
+        .filter(|event| matches!(event.new, LaserMode::Ready))
+        .map(|event| event.entity)
+        .collect();
+    let distance = clocks.get(Clock::Trial).delta().as_secs_f32();
+    for (entity, mut position, laser, mut tracker, phases) in &mut laser_query {
+        if !matches!(phases.mode(), LaserMode::Ready) {
+            continue;
+        }
+        // lines are rows for horizontal lasers and columns for vertical ones
+        let line = |vec: IVec2| match laser.axis {
+            Axis::Horizontal => vec.y,
+            _ => vec.x,
+        };
+        let (min, max) = (line(board.min), line(board.max));
+        let current = line(position.vec);
+        let target = laser
+            .target
+            .and_then(|target| player_query.get(target).ok());
+        let tracking = tracker.tracking;
+        let next = match tracking {
+            Tracking::Snap => target.map(|(player_position, ..)| line(player_position.vec)),
+            Tracking::Follow(speed) => target.map(|(player_position, ..)| {
+                tracker.follow(current, line(player_position.vec), speed * distance)
+            }),
+            // players standing still are aimed at, not the tile they last moved towards
+            Tracking::Predict => target.map(|(player_position, moves, tween)| {
+                let heading = moves.heading(tween.is_travelling());
+                let ahead = heading.map_or(IVec2::ZERO, |heading| heading.vec());
+                line(player_position.vec + ahead).clamp(min, max)
+            }),
+            Tracking::Random => readied.contains(&entity).then(|| fastrand::i32(min..=max)),
+            Tracking::Sweep(speed) => Some(tracker.sweep(current, min, max, speed * distance)),
+        };
+        let Some(next) = next else {
+            continue;
+        };
+        match laser.axis {
+            Axis::Horizontal => position.vec.y = next,
+            Axis::Vertical => position.vec.x = next,
+            // the loader keeps mobile lasers to rows and columns
+            Axis::Diagonal | Axis::AntiDiagonal => {}
+        }
+    }
+}
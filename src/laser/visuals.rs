@@ -6,13 +6,14 @@ use bevy::{
 };
 
 use crate::{
-    background::Countdown,
+    intensity::Intensity,
     laser::{Laser, LaserMode},
     material_from_color,
-    phases::{self, Phases},
+    phases::{self, PhaseEntered, Phases},
     player::Player,
     post_processing::PostProcessing,
-    HIGH_CHROMATIC_ABERRATION, LOW_CHROMATIC_ABERRATION, MEDIUM_CHROMATIC_ABERRATION,
+    track::{self, Choreography},
+    LOW_CHROMATIC_ABERRATION, MEDIUM_CHROMATIC_ABERRATION,
 };
 
 pub struct VisualsPlugin;
@@ -20,13 +21,19 @@ pub struct VisualsPlugin;
 impl Plugin for VisualsPlugin {
     fn build(&self, app: &mut App) {
         app.add_system(charge.after(phases::transition::<LaserMode>))
-            .add_system(attack.after(phases::transition::<LaserMode>))
-            .add_system(attack_sound.after(phases::transition::<LaserMode>));
+            .add_system(
+                attack
+                    .after(phases::transition::<LaserMode>)
+                    .after(track::mix),
+            )
+            .add_system(attack_sound.after(phases::transition::<LaserMode>))
+            .add_system(turn.after(super::threaten));
     }
 }
 
 pub fn turrets_blueprint(
     mobile: bool,
+    reach: f32,
     commands: &mut Commands,
     color: Color,
     meshes: &mut Assets<Mesh>,
@@ -36,25 +43,25 @@ pub fn turrets_blueprint(
     let top = MaterialMeshBundle {
         mesh: meshes.add(Mesh::from(shape::Box::new(0.3, 0.3, 0.6))),
         material: materials.add(material_from_color(color)),
-        transform: Transform::from_xyz(0.0, 0.0, 2.0),
+        transform: Transform::from_xyz(0.0, 0.0, reach),
         ..default()
     };
     let bottom = MaterialMeshBundle {
         mesh: meshes.add(Mesh::from(shape::Box::new(0.3, 0.3, 0.6))),
         material: materials.add(material_from_color(color)),
-        transform: Transform::from_xyz(0.0, 0.0, -2.0),
+        transform: Transform::from_xyz(0.0, 0.0, -reach),
         ..default()
     };
     let top_rail = MaterialMeshBundle {
         mesh: meshes.add(Mesh::from(shape::Box::new(0.6, 0.15, 0.3))),
         material: materials.add(material_from_color(color)),
-        transform: Transform::from_xyz(0.0, 0.0, 2.0),
+        transform: Transform::from_xyz(0.0, 0.0, reach),
         ..default()
     };
     let bottom_rail = MaterialMeshBundle {
         mesh: meshes.add(Mesh::from(shape::Box::new(0.6, 0.15, 0.3))),
         material: materials.add(material_from_color(color)),
-        transform: Transform::from_xyz(0.0, 0.0, -2.0),
+        transform: Transform::from_xyz(0.0, 0.0, -reach),
         ..default()
     };
     commands
@@ -71,6 +78,7 @@ pub fn turrets_blueprint(
 }
 
 pub fn ray_blueprint(
+    reach: f32,
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
@@ -78,14 +86,11 @@ pub fn ray_blueprint(
     let root = (
         MaterialMeshBundle {
             mesh: meshes.add(Mesh::from(shape::Capsule {
-                depth: 4.0,
+                depth: 2.0 * reach,
                 radius: 0.075,
                 ..default()
             })),
-            material: materials.add(StandardMaterial {
-                emissive: Color::rgb(1.0, 0.02, 0.03) * 3.5,
-                ..material_from_color(Color::rgb(1.0, 0.1, 0.12))
-            }),
+            material: materials.add(ray_material()),
             transform: Transform::from_rotation(Quat::from_rotation_x(PI / 2.0)),
             ..default()
         },
@@ -95,8 +100,16 @@ pub fn ray_blueprint(
     commands.spawn(root).id()
 }
 
+pub fn ray_material() -> StandardMaterial {
+    StandardMaterial {
+        emissive: Color::rgb(1.0, 0.02, 0.03) * 3.5,
+        ..material_from_color(Color::rgb(1.0, 0.1, 0.12))
+    }
+}
+
 #[derive(Component)]
 pub struct Visuals {
+    pub model: Entity,
     pub normal: Entity,
     pub fast: Entity,
     pub charging: Entity,
@@ -104,8 +117,9 @@ pub struct Visuals {
 }
 
 impl Visuals {
-    pub fn new(normal: Entity, fast: Entity, charging: Entity, ray: Entity) -> Self {
+    pub fn new(model: Entity, normal: Entity, fast: Entity, charging: Entity, ray: Entity) -> Self {
         Self {
+            model,
             normal,
             fast,
             charging,
@@ -129,37 +143,51 @@ fn charge(
     }
 }
 
+fn turn(laser_query: Query<(&Laser, &Visuals)>, mut transform_query: Query<&mut Transform>) {
+    for (laser, visuals) in &laser_query {
+        if laser.sweep.is_some() {
+            let mut transform = transform_query.get_mut(visuals.model).unwrap();
+            transform.rotation = Quat::from_rotation_y(laser.angle);
+        }
+    }
+}
+
 fn attack(
     laser_query: Query<(&Phases<LaserMode>, &Visuals), Without<Player>>,
     mut visibility_query: Query<&mut Visibility>,
     mut post_processing_query: Query<&mut PostProcessing>,
-    countdown: Res<Countdown>,
+    intensity: Res<Intensity>,
+    choreography: Res<Choreography>,
 ) {
     let mut shooters = 0;
     for (phases, visuals) in &laser_query {
         let shooting = matches!(phases.mode(), LaserMode::Attacking);
         let mut visibility = visibility_query.get_mut(visuals.ray).unwrap();
         visibility.is_visible = shooting;
-        shooters += shooting as i32;
+        shooters += shooting as usize;
     }
     let mut post_processing = post_processing_query.single_mut();
-    post_processing.aberration = match shooters > 0 {
-        true => match countdown.timer.elapsed_secs() >= 16.0 && shooters >= 3 {
-            true => HIGH_CHROMATIC_ABERRATION,
-            false => MEDIUM_CHROMATIC_ABERRATION,
-        },
-        false => LOW_CHROMATIC_ABERRATION,
+    let stage = &intensity.stage;
+    let aberration = match shooters {
+        0 => LOW_CHROMATIC_ABERRATION,
+        n if n >= stage.crowd => stage.aberration.value(),
+        _ => MEDIUM_CHROMATIC_ABERRATION,
     };
+    post_processing.aberration = aberration + choreography.aberration;
 }
 
 pub fn attack_sound(
-    query: Query<(&Laser, &Phases<LaserMode>)>,
+    mut events: EventReader<PhaseEntered<LaserMode>>,
+    query: Query<&Laser>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
 ) {
-    for (laser, phases) in &query {
-        if laser.mobile && phases.just_transitioned {
-            let (path, volume) = match phases.mode() {
+    for event in events.iter() {
+        let Ok(laser) = query.get(event.entity) else {
+            continue;
+        };
+        if laser.mobile {
+            let (path, volume) = match event.new {
                 LaserMode::Charging => ("sounds/charge.ogg", 0.1),
                 LaserMode::Attacking => ("sounds/attack.ogg", 0.2),
                 LaserMode::Ready => continue,
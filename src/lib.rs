@@ -1,21 +1,51 @@
+mod actions;
 mod background;
 mod board;
+mod bomb;
+mod campaign;
+mod clock;
+mod easing;
+mod hazard;
+mod intensity;
 mod laser;
+mod loader;
 mod palette;
 mod phases;
 mod player;
 mod post_processing;
+mod settings;
+mod timeline;
+mod track;
+mod trial;
+mod validation;
 
 use background::BackgroundPlugin;
 use bevy::{
     core_pipeline::{bloom::BloomSettings, fxaa::Fxaa},
     prelude::*,
+    window::WindowFocused,
 };
 use post_processing::{PostProcessing, PostProcessingPlugin};
 
-use self::{board::BoardPlugin, laser::LaserPlugin, player::PlayerPlugin};
+use self::{
+    actions::{Action, Actions, ActionsPlugin},
+    board::{Board, BoardPlugin},
+    bomb::BombPlugin,
+    campaign::CampaignPlugin,
+    clock::{Clock, ClockPlugin, Clocks},
+    hazard::HazardPlugin,
+    intensity::IntensityPlugin,
+    laser::LaserPlugin,
+    player::PlayerPlugin,
+    settings::{Settings, SettingsPlugin},
+    track::{Choreography, TrackPlugin},
+    trial::{CurrentTrial, Trial, TrialPlugin},
+};
 
 const SHADOW_SIZE: f32 = 11.0;
+const CAMERA_OFFSET: Vec3 = Vec3::new(5.0, 15.0, 15.0);
+const FRAMED_TILES: f32 = 3.0;
+const AMBIENT_BRIGHTNESS: f32 = 0.1;
 const LOW_CHROMATIC_ABERRATION: f32 = 0.0;
 const MEDIUM_CHROMATIC_ABERRATION: f32 = 0.001;
 const HIGH_CHROMATIC_ABERRATION: f32 = 0.003;
@@ -25,14 +55,33 @@ pub struct AppPlugin;
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
         app.add_state(AppState::Splash)
+            .add_plugin(ClockPlugin)
+            .add_plugin(SettingsPlugin)
+            .add_plugin(ActionsPlugin)
+            .add_plugin(TrialPlugin)
+            .add_plugin(CampaignPlugin)
+            .add_plugin(TrackPlugin)
+            .add_plugin(IntensityPlugin)
             .add_plugin(BackgroundPlugin)
             .add_plugin(BoardPlugin)
             .add_plugin(PlayerPlugin)
+            .add_plugin(HazardPlugin)
             .add_plugin(LaserPlugin)
+            .add_plugin(BombPlugin)
             .add_plugin(PostProcessingPlugin)
             .insert_resource(Msaa { samples: 1 })
+            .insert_resource(AfterTeardown::new(AppState::Setup))
             .add_startup_system(setup)
             .add_system_set(SystemSet::on_update(AppState::Splash).with_system(start))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup).with_system(frame.after(board::enter_setup)),
+            )
+            .add_system(choreograph.after(track::mix))
+            .add_system_set(SystemSet::on_update(AppState::Start).with_system(pause))
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(pause))
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(enter_paused))
+            .add_system_set(SystemSet::on_update(AppState::Paused).with_system(pause_menu))
+            .add_system_set(SystemSet::on_exit(AppState::Paused).with_system(exit_paused))
             .add_system_set(SystemSet::on_update(AppState::Defeat).with_system(restart))
             .add_system_set(SystemSet::on_update(AppState::Victory).with_system(restart));
     }
@@ -44,6 +93,8 @@ pub enum AppState {
     Setup,
     Start,
     Game,
+    // pushed over `Start` or `Game`, which resume once it is popped
+    Paused,
     Defeat,
     Victory,
     Teardown,
@@ -57,12 +108,13 @@ fn setup(mut commands: Commands) {
                 hdr: true,
                 ..default()
             },
-            transform: Transform::from_xyz(5.0, 15.0, 15.0).looking_at(Vec3::ZERO, Vec3::Y),
+            transform: Transform::from_translation(CAMERA_OFFSET).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
         },
         UiCameraConfig { show_ui: false },
         Fxaa::default(),
         PostProcessing::new(LOW_CHROMATIC_ABERRATION),
+        Framing::new(CAMERA_OFFSET),
     ));
     #[cfg(not(target_arch = "wasm32"))]
     camera.insert(BloomSettings {
@@ -72,7 +124,7 @@ fn setup(mut commands: Commands) {
     });
     // lights
     commands.insert_resource(AmbientLight {
-        brightness: 0.1,
+        brightness: AMBIENT_BRIGHTNESS,
         ..default()
     });
     for i in [-6.0, 4.0] {
@@ -83,15 +135,7 @@ fn setup(mut commands: Commands) {
                 directional_light: DirectionalLight {
                     illuminance: ((i.abs() - 3.0) * 2.0 + (8.0 - j.abs()) * 1.5) * 1500.0,
                     shadows_enabled: true,
-                    shadow_projection: OrthographicProjection {
-                        left: -SHADOW_SIZE,
-                        right: SHADOW_SIZE,
-                        bottom: -SHADOW_SIZE * 1.5,
-                        top: SHADOW_SIZE,
-                        near: -SHADOW_SIZE,
-                        far: SHADOW_SIZE,
-                        ..default()
-                    },
+                    shadow_projection: shadow_projection(SHADOW_SIZE),
                     ..default()
                 },
                 ..default()
@@ -100,18 +144,135 @@ fn setup(mut commands: Commands) {
     }
 }
 
-fn start(mut input: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
-    if input.just_pressed(KeyCode::Space) {
+fn shadow_projection(size: f32) -> OrthographicProjection {
+    OrthographicProjection {
+        left: -size,
+        right: size,
+        bottom: -size * 1.5,
+        top: size,
+        near: -size,
+        far: size,
+        ..default()
+    }
+}
+
+fn frame(
+    mut camera_query: Query<(&mut Transform, &mut Framing), With<Camera3d>>,
+    mut light_query: Query<(&mut Transform, &mut DirectionalLight), Without<Camera3d>>,
+    board: Res<Board>,
+) {
+    let size = board.size();
+    let scale = f32::max(size.x.max(size.y) as f32 / FRAMED_TILES, 1.0);
+    let center = Vec3::new(board.center().x, 0.0, -board.center().y);
+    let (mut camera_transform, mut framing) = camera_query.single_mut();
+    framing.translation = center + CAMERA_OFFSET * scale;
+    *camera_transform =
+        Transform::from_translation(framing.translation).looking_at(center, Vec3::Y);
+    for (mut light_transform, mut light) in &mut light_query {
+        light_transform.translation = center;
+        light.shadow_projection = shadow_projection(SHADOW_SIZE * scale);
+    }
+}
+
+fn choreograph(
+    mut camera_query: Query<(&mut Transform, &Framing), With<Camera3d>>,
+    mut ambient: ResMut<AmbientLight>,
+    choreography: Res<Choreography>,
+) {
+    let (mut camera_transform, framing) = camera_query.single_mut();
+    // the offset pans the camera without changing where it looks
+    camera_transform.translation = framing.translation + choreography.camera;
+    ambient.brightness = choreography.ambient;
+    ambient.color = choreography.ambient_color;
+}
+
+#[derive(Component)]
+struct Framing {
+    translation: Vec3,
+}
+
+impl Framing {
+    fn new(translation: Vec3) -> Self {
+        Self { translation }
+    }
+}
+
+fn start(
+    mut actions: ResMut<Actions>,
+    mut state: ResMut<State<AppState>>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+) {
+    // the trial might still be loading or have failed to parse
+    if actions.just_pressed(Action::Confirm) && trials.contains(&current_trial.handle) {
         state.overwrite_set(AppState::Setup).unwrap();
-        input.clear(); // avoids infinite loops until stageless
+        actions.clear(); // avoids infinite loops until stageless
     }
 }
 
-fn restart(mut input: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
-    if input.just_pressed(KeyCode::Space) {
+fn restart(
+    mut actions: ResMut<Actions>,
+    mut state: ResMut<State<AppState>>,
+    mut after_teardown: ResMut<AfterTeardown>,
+) {
+    if actions.just_pressed(Action::Confirm) {
         state.overwrite_set(AppState::Teardown).unwrap();
-        input.clear(); // avoids infinite loops until stageless
+        after_teardown.state = AppState::Setup;
+        actions.clear(); // avoids infinite loops until stageless
+    }
+}
+
+#[derive(Resource)]
+pub struct AfterTeardown {
+    pub state: AppState,
+}
+
+impl AfterTeardown {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+fn pause(
+    mut actions: ResMut<Actions>,
+    mut focus_events: EventReader<WindowFocused>,
+    mut state: ResMut<State<AppState>>,
+    settings: Res<Settings>,
+) {
+    let unfocused = focus_events.iter().any(|event| !event.focused);
+    if actions.just_pressed(Action::Pause) || (settings.auto_pause && unfocused) {
+        state.overwrite_push(AppState::Paused).unwrap();
+        actions.clear(); // avoids infinite loops until stageless
+    }
+}
+
+fn enter_paused(mut clocks: ResMut<Clocks>) {
+    // the countdown and every phase following the trial clock stop with it
+    clocks.get_mut(Clock::Trial).paused = true;
+}
+
+fn exit_paused(mut clocks: ResMut<Clocks>) {
+    clocks.get_mut(Clock::Trial).paused = false;
+}
+
+fn pause_menu(
+    mut actions: ResMut<Actions>,
+    mut state: ResMut<State<AppState>>,
+    mut after_teardown: ResMut<AfterTeardown>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        state.overwrite_pop().unwrap();
+    } else if actions.just_pressed(Action::Confirm) {
+        // replacing the whole stack exits the paused trial as well
+        state.overwrite_replace(AppState::Teardown).unwrap();
+        after_teardown.state = AppState::Setup;
+    } else if actions.just_pressed(Action::Quit) {
+        state.overwrite_replace(AppState::Teardown).unwrap();
+        after_teardown.state = AppState::Splash;
+    } else {
+        return;
     }
+    actions.clear(); // avoids infinite loops until stageless
 }
 
 pub fn material_from_color(color: Color) -> StandardMaterial {
@@ -0,0 +1,48 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, LoadContext, LoadedAsset},
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+// assets written in ron, built from the data in the file
+pub trait RonAsset: Asset + Sized {
+    type Data: DeserializeOwned;
+    const EXTENSIONS: &'static [&'static str];
+
+    fn build(data: Self::Data) -> Result<Self, String>;
+}
+
+pub struct RonLoader<T> {
+    asset: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for RonLoader<T> {
+    fn default() -> Self {
+        Self { asset: PhantomData }
+    }
+}
+
+impl<T: RonAsset> AssetLoader for RonLoader<T> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().display();
+            // ron errors already carry the line and column
+            let data = ron::de::from_bytes::<T::Data>(bytes)
+                .map_err(|error| bevy::asset::Error::msg(format!("{}:{}", path, error)))?;
+            let asset = T::build(data)
+                .map_err(|error| bevy::asset::Error::msg(format!("{}: {}", path, error)))?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
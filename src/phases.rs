@@ -1,54 +1,168 @@
-use std::time::Duration;
+use std::{fmt::Debug, marker::PhantomData, time::Duration};
 
 use bevy::prelude::*;
 
-use crate::background::Countdown;
+use crate::{
+    background::{self, Countdown},
+    clock::{Clock, Clocks},
+    easing::Easing,
+};
 
-pub struct PhasesPlugin;
+pub struct PhasesPlugin<T> {
+    phantom: PhantomData<T>,
+}
 
-impl Plugin for PhasesPlugin {
-    fn build(&self, _: &mut App) {}
+impl<T> Default for PhasesPlugin<T> {
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone + Copy + Debug + Send + Sync + 'static> Plugin for PhasesPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PhaseEntered<T>>()
+            .add_event::<PhaseExited<T>>()
+            .add_system(transition::<T>.after(background::countdown))
+            .add_system(trace_transitions::<T>.after(transition::<T>));
+    }
 }
 
 #[derive(Component)]
 pub struct Phases<T: Send + Sync + 'static> {
     pub vec: Vec<Phase<T>>,
+    pub index: usize,
     pub start: Duration,
     pub progress: f32,
-    pub just_reset: bool,
-    pub just_transitioned: bool,
+    pub clock: Clock,
+    // mode replaced by a reset or a seek, pending to be reported by `transition`
+    replaced: Option<T>,
+    // time of the timeline replaced by a reset or a seek, pending to be matched with the clock
+    pending_time: Option<Duration>,
+    // timeline and clock times at the last reset or seek, for clocks other than the trial
+    anchor: (Duration, Duration),
 }
 
 impl<T: Clone + Copy + Send + Sync> Phases<T> {
     pub fn new(base: T) -> Self {
         Self {
             vec: vec![Phase::new(base, 0.0)],
+            index: 0,
             start: Duration::ZERO,
             progress: 0.0,
-            just_reset: true,
-            just_transitioned: true,
+            clock: Clock::Trial,
+            replaced: None,
+            pending_time: None,
+            anchor: (Duration::ZERO, Duration::ZERO),
         }
     }
 
     pub fn mode(&self) -> T {
-        match self.vec.first() {
+        match self.vec.get(self.index) {
             Some(phase) => phase.mode,
             None => unreachable!(),
         }
     }
 
     pub fn reset(&mut self, vec: Vec<Phase<T>>) {
+        // keeps the oldest mode if the phases are reset twice in the same frame
+        self.replaced = self.replaced.or(Some(self.mode()));
+        self.pending_time = Some(Duration::ZERO);
         self.vec = vec;
+        self.index = 0;
         self.start = Duration::ZERO;
         self.progress = 0.0;
-        self.just_reset = true;
-        self.just_transitioned = true;
+    }
+
+    pub fn seek(&mut self, time: Duration) {
+        self.replaced = self.replaced.or(Some(self.mode()));
+        self.pending_time = Some(time);
+        self.index = 0;
+        self.start = Duration::ZERO;
+        while !self.is_last() && time >= self.end() {
+            self.start = self.end();
+            self.index += 1;
+        }
+        self.update_progress(time);
+    }
+
+    pub fn eased_progress(&self) -> f32 {
+        self.vec[self.index].easing.ease(self.progress)
+    }
+
+    fn time(&mut self, countdown: &Countdown, clocks: &Clocks) -> (Duration, bool) {
+        if self.clock == Clock::Trial {
+            self.pending_time = None;
+            return (countdown.timer.elapsed(), countdown.timer.finished());
+        }
+        let clock_time = clocks.get(self.clock).elapsed();
+        if let Some(time) = self.pending_time.take() {
+            self.anchor = (time, clock_time);
+        }
+        let (time, anchor_clock_time) = self.anchor;
+        (time + clock_time.saturating_sub(anchor_clock_time), false)
+    }
+
+    fn is_last(&self) -> bool {
+        self.index + 1 >= self.vec.len()
+    }
+
+    fn end(&self) -> Duration {
+        self.start + self.vec[self.index].duration
+    }
+
+    fn update_progress(&mut self, time: Duration) {
+        let elapsed = time.saturating_sub(self.start);
+        let duration = self.vec[self.index].duration;
+        self.progress = match duration {
+            Duration::ZERO => 1.0,
+            _ => f32::min(elapsed.as_secs_f32() / duration.as_secs_f32(), 1.0),
+        };
     }
 }
 
+pub struct PhaseExited<T> {
+    pub entity: Entity,
+    pub old: T,
+    pub new: T,
+    pub timestamp: Duration,
+}
+
+impl<T> PhaseExited<T> {
+    pub fn new(entity: Entity, old: T, new: T, timestamp: Duration) -> Self {
+        Self {
+            entity,
+            old,
+            new,
+            timestamp,
+        }
+    }
+}
+
+pub struct PhaseEntered<T> {
+    pub entity: Entity,
+    pub old: T,
+    pub new: T,
+    pub timestamp: Duration,
+}
+
+impl<T> PhaseEntered<T> {
+    pub fn new(entity: Entity, old: T, new: T, timestamp: Duration) -> Self {
+        Self {
+            entity,
+            old,
+            new,
+            timestamp,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Phase<T> {
     pub mode: T,
     pub duration: Duration,
+    pub easing: Easing,
 }
 
 impl<T> Phase<T> {
@@ -56,33 +170,65 @@ impl<T> Phase<T> {
         Self {
             mode,
             duration: Duration::from_secs_f32(seconds),
+            easing: Easing::Linear,
         }
     }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
 }
 
-pub fn transition<T: Send + Sync>(mut query: Query<&mut Phases<T>>, countdown: Res<Countdown>) {
-    for mut phases in &mut query {
-        match phases.just_reset {
-            true => phases.just_reset = false,
-            false => phases.just_transitioned = false,
+pub fn transition<T: Clone + Copy + Send + Sync>(
+    mut query: Query<(Entity, &mut Phases<T>)>,
+    mut exited_events: EventWriter<PhaseExited<T>>,
+    mut entered_events: EventWriter<PhaseEntered<T>>,
+    countdown: Res<Countdown>,
+    clocks: Res<Clocks>,
+) {
+    for (entity, mut phases) in &mut query {
+        if let Some(old) = phases.replaced.take() {
+            let new = phases.mode();
+            let timestamp = phases.start;
+            exited_events.send(PhaseExited::new(entity, old, new, timestamp));
+            entered_events.send(PhaseEntered::new(entity, old, new, timestamp));
         }
-        if phases.vec.len() <= 1 {
-            continue;
+        let (time, finished) = phases.time(&countdown, &clocks);
+        // catches up with every phase that ended since the last frame
+        while !phases.is_last() {
+            let ended = time >= phases.end();
+            if !ended && !finished {
+                break;
+            }
+            let old = phases.mode();
+            phases.start = phases.end();
+            phases.index += 1;
+            let new = phases.mode();
+            let timestamp = phases.start;
+            exited_events.send(PhaseExited::new(entity, old, new, timestamp));
+            entered_events.send(PhaseEntered::new(entity, old, new, timestamp));
         }
-        let duration = phases.vec.first().unwrap().duration;
-        if countdown.timer.elapsed() >= phases.start + duration || countdown.timer.finished() {
-            phases.start += duration;
-            phases.vec.remove(0);
-            phases.just_transitioned = true;
-        }
-        let elapsed = match countdown.timer.elapsed() >= phases.start {
-            true => countdown.timer.elapsed() - phases.start,
-            false => Duration::ZERO,
-        };
-        let duration = phases.vec.first().unwrap().duration;
-        phases.progress = match duration {
-            Duration::ZERO => 1.0,
-            _ => f32::min(elapsed.as_secs_f32() / duration.as_secs_f32(), 1.0),
-        };
+        phases.update_progress(time);
+    }
+}
+
+fn trace_transitions<T: Debug + Send + Sync + 'static>(
+    mut exited_events: EventReader<PhaseExited<T>>,
+    mut entered_events: EventReader<PhaseEntered<T>>,
+) {
+    for event in exited_events.iter() {
+        let (entity, old, new) = (event.entity, &event.old, &event.new);
+        trace!(
+            "{entity:?} exited {old:?} for {new:?} at {:?}",
+            event.timestamp
+        );
+    }
+    for event in entered_events.iter() {
+        let (entity, old, new) = (event.entity, &event.old, &event.new);
+        trace!(
+            "{entity:?} entered {new:?} from {old:?} at {:?}",
+            event.timestamp
+        );
     }
 }
@@ -1,9 +1,17 @@
+use std::{collections::VecDeque, time::Duration};
+
 use bevy::prelude::*;
+use serde::Deserialize;
 
 use crate::{
-    board::{Board, BoardMode, Position, HIDDEN_HEIGHT},
-    laser, material_from_color, palette,
+    actions::{Action, Actions, Direction},
+    board::{self, Board, BoardMode, Position, Tween, HIDDEN_HEIGHT},
+    clock::{Clock, Clocks},
+    easing::Easing,
+    hazard, material_from_color, palette,
     phases::{Phase, Phases},
+    settings::Settings,
+    trial::{CurrentTrial, Trial},
     AppState,
 };
 
@@ -15,27 +23,45 @@ const PLAYER_ALIVE_COLORS: [Color; 5] = [
     palette::DARK_PINK,
 ];
 const PLAYER_DEAD_COLOR: Color = palette::DARK_BLACK;
+const MODEL_HEIGHT: f32 = 0.4;
+const BLINKS_PER_SECOND: f32 = 8.0;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Health>()
+        app.add_event::<Damage>()
+            .init_resource::<Winner>()
             .add_startup_system(setup)
-            .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(enter_setup))
-            .add_system_set(SystemSet::on_enter(AppState::Defeat).with_system(enter_defeat))
+            .add_system(motion)
+            .add_system(hop.after(board::tween))
+            .add_system(invulnerability)
+            .add_system(recharge)
+            .add_system(blink.after(invulnerability))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(enter_setup.after(board::enter_setup)),
+            )
             .add_system_set(SystemSet::on_enter(AppState::Teardown).with_system(enter_teardown))
+            .add_system_set(SystemSet::on_exit(AppState::Teardown).with_system(exit_teardown))
             .add_system_set(
                 SystemSet::on_update(AppState::Start)
                     .with_system(start_trigger)
-                    .with_system(movement),
+                    .with_system(buffer)
+                    .with_system(movement.after(buffer)),
             )
             .add_system_set(
                 SystemSet::on_update(AppState::Game)
-                    .with_system(movement)
-                    .with_system(defeat.after(laser::attack)),
+                    .with_system(buffer)
+                    .with_system(movement.after(buffer))
+                    .with_system(damage.after(hazard::hit))
+                    .with_system(outcome.after(damage)),
             )
-            .add_system_set(SystemSet::on_update(AppState::Victory).with_system(movement));
+            .add_system_set(
+                SystemSet::on_update(AppState::Victory)
+                    .with_system(buffer)
+                    .with_system(movement.after(buffer)),
+            );
     }
 }
 
@@ -44,118 +70,408 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let model = commands
-        .spawn(MaterialMeshBundle {
-            mesh: meshes.add(Mesh::from(shape::Cube::new(0.8))),
-            material: materials.add(material_from_color(PLAYER_ALIVE_COLORS[0])),
-            transform: Transform::from_xyz(0.0, 0.4, 0.0),
-            ..default()
-        })
-        .id();
-    let root = (
-        SpatialBundle::from_transform(Transform::from_xyz(0.0, HIDDEN_HEIGHT, 0.0)),
-        Position::from_xy(0, 0),
-        Player::new(model),
-        Phases::new(BoardMode::Hidden),
-    );
-    commands.spawn(root).push_children(&[model]);
+    commands.insert_resource(Looks {
+        mesh: meshes.add(Mesh::from(shape::Cube::new(0.8))),
+        alive: PLAYER_ALIVE_COLORS.map(|color| materials.add(material_from_color(color))),
+        dead: materials.add(material_from_color(PLAYER_DEAD_COLOR * 0.5)),
+    });
+}
+
+#[derive(Resource)]
+struct Looks {
+    mesh: Handle<Mesh>,
+    alive: [Handle<StandardMaterial>; PLAYER_ALIVE_COLORS.len()],
+    dead: Handle<StandardMaterial>,
 }
 
 fn enter_setup(
-    mut player_query: Query<(&mut Position, &mut Player, &mut Phases<BoardMode>)>,
-    mut material_query: Query<&mut Handle<StandardMaterial>>,
-    mut health: ResMut<Health>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+    looks: Res<Looks>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+    mut colors: Local<Vec<usize>>,
 ) {
-    let (mut position, mut player, mut phases) = player_query.single_mut();
-    position.vec = IVec2::ZERO;
-    phases.reset(vec![
-        Phase::new(BoardMode::Waiting, 0.4),  // 0.4
-        Phase::new(BoardMode::Entering, 1.0), // 1.4
-        Phase::new(BoardMode::Shown, 0.0),    // final
-    ]);
-    let mut handle = material_query.get_mut(player.model).unwrap();
-    *handle = materials.add(material_from_color(PLAYER_ALIVE_COLORS[player.color]));
-    let mut color = player.color;
-    while color == player.color {
-        color = fastrand::usize(..PLAYER_ALIVE_COLORS.len());
-    }
-    player.color = color;
-    health.dead = false;
-}
-
-fn enter_defeat(
-    player_query: Query<&Player>,
-    mut material_query: Query<&mut Handle<StandardMaterial>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    let trial = trials.get(&current_trial.handle).unwrap();
+    let count = settings.bindings.len().clamp(1, PLAYER_ALIVE_COLORS.len());
+    // players start on the tiles closest to the origin, the first one on the origin itself
+    let mut starts: Vec<IVec2> = board.tiles.iter().copied().collect();
+    starts.sort_by_key(|tile| (tile.x * tile.x + tile.y * tile.y, tile.y, tile.x));
+    let mut taken = Vec::new();
+    for index in 0..count {
+        let color = next_color(colors.get(index).copied(), &taken);
+        taken.push(color);
+        let start = starts[index % starts.len()];
+        let model = commands
+            .spawn(MaterialMeshBundle {
+                mesh: looks.mesh.clone(),
+                material: looks.alive[color].clone(),
+                transform: Transform::from_xyz(0.0, MODEL_HEIGHT, 0.0),
+                ..default()
+            })
+            .id();
+        let mut phases = Phases::new(BoardMode::Hidden);
+        phases.reset(vec![
+            Phase::new(BoardMode::Waiting, 0.3 + board.stagger(start)),
+            Phase::new(BoardMode::Entering, 1.0).with_easing(Easing::BackInOut),
+            Phase::new(BoardMode::Shown, 0.0), // final
+        ]);
+        let points = trial.health.points + settings.extra_health;
+        let root = (
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, HIDDEN_HEIGHT, 0.0)),
+            Position::new(start),
+            Player::new(index, start, PLAYER_ALIVE_COLORS[color], model),
+            Moves::new(),
+            Tween::new(settings.move_motion, start),
+            Health::new(points, trial.health.invulnerability),
+            phases,
+        );
+        let mut entity = commands.spawn(root);
+        entity.push_children(&[model]);
+        if let Some(dash) = &trial.dash {
+            entity.insert(Dash::new(dash.distance, dash.cooldown));
+        }
+    }
+    *colors = taken;
+}
+
+fn next_color(previous: Option<usize>, taken: &[usize]) -> usize {
+    let free: Vec<usize> = (0..PLAYER_ALIVE_COLORS.len())
+        .filter(|color| !taken.contains(color))
+        .collect();
+    let fresh: Vec<usize> = free
+        .iter()
+        .copied()
+        .filter(|&color| Some(color) != previous)
+        .collect();
+    match (previous, fresh.is_empty()) {
+        (None, _) => free[0],
+        (Some(_), true) => free[fastrand::usize(..free.len())],
+        (Some(_), false) => fresh[fastrand::usize(..fresh.len())],
+    }
+}
+
+fn enter_teardown(
+    mut query: Query<(&Position, &mut Phases<BoardMode>), With<Player>>,
+    board: Res<Board>,
 ) {
-    let player = player_query.single();
-    let mut handle = material_query.get_mut(player.model).unwrap();
-    *handle = materials.add(material_from_color(PLAYER_DEAD_COLOR * 0.5));
+    for (position, mut phases) in &mut query {
+        phases.reset(vec![
+            Phase::new(BoardMode::Waiting, board.stagger(position.vec)),
+            Phase::new(BoardMode::Exiting, 1.0).with_easing(Easing::BackInOut),
+            Phase::new(BoardMode::Hidden, 0.0), // final
+        ]);
+    }
 }
 
-fn enter_teardown(mut query: Query<(&Position, &mut Phases<BoardMode>), With<Player>>) {
-    let (position, mut phases) = query.single_mut();
-    let offset = position.vec.x - position.vec.y;
-    phases.reset(vec![
-        Phase::new(BoardMode::Waiting, 0.1 + offset as f32 * 0.05),
-        Phase::new(BoardMode::Exiting, 1.0),
-        Phase::new(BoardMode::Hidden, 0.0), // final
-    ]);
+fn exit_teardown(
+    mut commands: Commands,
+    query: Query<Entity, With<Player>>,
+    mut winner: ResMut<Winner>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    winner.index = None;
 }
 
-#[derive(Resource, Default)]
+#[derive(Component)]
 pub struct Health {
-    pub dead: bool,
+    pub points: u32,
+    invulnerability: Timer,
+}
+
+impl Health {
+    pub fn new(points: u32, invulnerability: f32) -> Self {
+        let mut timer = Timer::from_seconds(invulnerability, TimerMode::Once);
+        // players start vulnerable
+        timer.tick(timer.duration());
+        Self {
+            points,
+            invulnerability: timer,
+        }
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        !self.invulnerability.finished()
+    }
+}
+
+// sent even if the player is invulnerable
+pub struct Damage {
+    pub target: Entity,
+    pub source: Entity,
+    pub amount: u32,
+}
+
+impl Damage {
+    pub fn new(target: Entity, source: Entity, amount: u32) -> Self {
+        Self {
+            target,
+            source,
+            amount,
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct Player {
+    pub index: usize,
+    pub start: IVec2,
+    pub color: Color,
     model: Entity,
-    color: usize,
 }
 
 impl Player {
-    pub fn new(alive: Entity) -> Self {
+    pub fn new(index: usize, start: IVec2, color: Color, model: Entity) -> Self {
         Self {
-            model: alive,
-            color: 0,
+            index,
+            start,
+            color,
+            model,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Winner {
+    pub index: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize)]
+pub enum Rules {
+    #[default]
+    Coop,
+    Versus,
+}
+
+// a lone player has no one to play against and goes through the trial as in coop
+pub fn versus(settings: &Settings, players: usize) -> bool {
+    settings.rules == Rules::Versus && players > 1
+}
+
+#[derive(Component)]
+pub struct Moves {
+    queue: VecDeque<Move>,
+    // last applied move, which dashes follow
+    facing: Direction,
+}
+
+impl Moves {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            facing: Direction::Up,
+        }
+    }
+
+    pub fn heading(&self, travelling: bool) -> Option<Direction> {
+        let next = self.queue.front().map(|next| next.direction);
+        next.or(travelling.then_some(self.facing))
+    }
+}
+
+struct Move {
+    direction: Direction,
+    dash: bool,
+    time: Duration,
+}
+
+#[derive(Component)]
+pub struct Dash {
+    distance: i32,
+    cooldown: Timer,
+}
+
+impl Dash {
+    pub fn new(distance: i32, cooldown: f32) -> Self {
+        let mut timer = Timer::from_seconds(cooldown, TimerMode::Once);
+        // players start with the dash ready
+        timer.tick(timer.duration());
+        Self {
+            distance,
+            cooldown: timer,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown.finished()
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.cooldown.remaining_secs()
+    }
+}
+
+fn buffer(mut query: Query<(&Player, &mut Moves)>, actions: Res<Actions>, settings: Res<Settings>) {
+    for (player, mut moves) in &mut query {
+        for action in actions.iter(player.index) {
+            let (direction, dash) = match action {
+                Action::Move(direction) => (direction, false),
+                // dashes follow the last move, pending or applied
+                Action::Dash => {
+                    let last = moves.queue.back().map(|last| last.direction);
+                    (last.unwrap_or(moves.facing), true)
+                }
+                _ => continue,
+            };
+            // presses beyond the buffer are dropped, keeping the older ones
+            if moves.queue.len() < settings.move_buffer_size {
+                let time = actions.time;
+                moves.queue.push_back(Move {
+                    direction,
+                    dash,
+                    time,
+                });
+            }
         }
     }
 }
 
 pub fn movement(
-    mut query: Query<&mut Position, With<Player>>,
+    mut query: Query<(&mut Position, &mut Moves, &Health, Option<&mut Dash>), With<Player>>,
     board: Res<Board>,
-    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
 ) {
-    let direction = IVec2::new(
-        input.just_pressed(KeyCode::Right) as i32 - input.just_pressed(KeyCode::Left) as i32,
-        input.just_pressed(KeyCode::Up) as i32 - input.just_pressed(KeyCode::Down) as i32,
-    );
-    if direction == IVec2::ZERO {
-        return;
+    let lifetime = Duration::from_secs_f32(settings.move_buffer_lifetime);
+    for (mut position, mut moves, health, mut dash) in &mut query {
+        // knocked out players stay where they fell
+        if health.points == 0 {
+            moves.queue.clear();
+            continue;
+        }
+        while let Some(next) = moves.queue.pop_front() {
+            if time.elapsed().saturating_sub(next.time) > lifetime {
+                continue;
+            }
+            let distance = match next.dash {
+                false => 1,
+                // dashes disabled or still recharging are dropped
+                true => match dash.as_deref() {
+                    Some(dash) if dash.is_ready() => dash.distance,
+                    _ => continue,
+                },
+            };
+            moves.facing = next.direction;
+            // moves stop at the edge of the board
+            let mut new_position = position.vec;
+            for _ in 0..distance {
+                let step = new_position + next.direction.vec();
+                if !board.tiles.contains(&step) {
+                    break;
+                }
+                new_position = step;
+            }
+            if new_position != position.vec {
+                position.vec = new_position;
+                if let (true, Some(dash)) = (next.dash, dash.as_mut()) {
+                    dash.cooldown.reset();
+                }
+                let sound = asset_server.load("sounds/step.ogg");
+                audio.play_with_settings(sound, PlaybackSettings::ONCE.with_volume(0.05));
+            }
+            break;
+        }
     }
-    let mut position = query.single_mut();
-    let new_position = position.vec + direction;
-    if board.tiles.contains(&new_position) {
-        position.vec = new_position;
-        let sound = asset_server.load("sounds/step.ogg");
-        audio.play_with_settings(sound, PlaybackSettings::ONCE.with_volume(0.05));
+}
+
+fn motion(mut query: Query<&mut Tween, With<Player>>, settings: Res<Settings>) {
+    if settings.is_changed() {
+        for mut tween in &mut query {
+            tween.motion = settings.move_motion;
+        }
+    }
+}
+
+fn hop(player_query: Query<(&Player, &Tween)>, mut transform_query: Query<&mut Transform>) {
+    for (player, tween) in &player_query {
+        let mut transform = transform_query.get_mut(player.model).unwrap();
+        transform.translation.y = MODEL_HEIGHT + tween.height();
     }
 }
 
-fn start_trigger(query: Query<&Position, With<Player>>, mut state: ResMut<State<AppState>>) {
-    let position = query.single();
-    if position.vec != IVec2::ZERO {
+fn start_trigger(query: Query<(&Position, &Player)>, mut state: ResMut<State<AppState>>) {
+    if query
+        .iter()
+        .any(|(position, player)| position.vec != player.start)
+    {
         state.overwrite_set(AppState::Game).unwrap();
     }
 }
 
-fn defeat(health: Res<Health>, mut state: ResMut<State<AppState>>) {
-    if health.dead {
+fn damage(
+    mut events: EventReader<Damage>,
+    mut player_query: Query<(&Player, &mut Health)>,
+    mut material_query: Query<&mut Handle<StandardMaterial>>,
+    looks: Res<Looks>,
+) {
+    for event in events.iter() {
+        let Ok((player, mut health)) = player_query.get_mut(event.target) else {
+            continue;
+        };
+        if health.is_invulnerable() || health.points == 0 {
+            continue;
+        }
+        debug!(
+            "{:?} took {} damage from {:?}",
+            event.target, event.amount, event.source
+        );
+        health.points = health.points.saturating_sub(event.amount);
+        // the lethal hit leaves no invulnerability to blink through
+        if health.points > 0 {
+            health.invulnerability.reset();
+        } else {
+            let mut handle = material_query.get_mut(player.model).unwrap();
+            *handle = looks.dead.clone();
+        }
+    }
+}
+
+fn invulnerability(mut query: Query<&mut Health>, clocks: Res<Clocks>) {
+    for mut health in &mut query {
+        health
+            .invulnerability
+            .tick(clocks.get(Clock::Trial).delta());
+    }
+}
+
+fn recharge(mut query: Query<&mut Dash>, clocks: Res<Clocks>) {
+    for mut dash in &mut query {
+        dash.cooldown.tick(clocks.get(Clock::Trial).delta());
+    }
+}
+
+fn blink(player_query: Query<(&Player, &Health)>, mut visibility_query: Query<&mut Visibility>) {
+    for (player, health) in &player_query {
+        let elapsed = health.invulnerability.elapsed_secs();
+        let visible = !health.is_invulnerable() || (elapsed * BLINKS_PER_SECOND).fract() < 0.5;
+        let mut visibility = visibility_query.get_mut(player.model).unwrap();
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+fn outcome(
+    query: Query<(&Player, &Health)>,
+    settings: Res<Settings>,
+    mut winner: ResMut<Winner>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let standing: Vec<&Player> = query
+        .iter()
+        .filter(|(_, health)| health.points > 0)
+        .map(|(player, _)| player)
+        .collect();
+    let versus = versus(&settings, query.iter().len());
+    if standing.is_empty() {
         state.overwrite_set(AppState::Defeat).unwrap();
+    } else if versus && standing.len() == 1 {
+        winner.index = Some(standing[0].index);
+        state.overwrite_set(AppState::Victory).unwrap();
     }
 }
@@ -0,0 +1,106 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::Deserialize;
+
+use crate::{
+    actions::Bindings,
+    board::{Motion, MotionStyle},
+    easing::Easing,
+    loader::{RonAsset, RonLoader},
+    player::Rules,
+};
+
+const SETTINGS_PATH: &str = "default.settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Settings>()
+            .init_asset_loader::<RonLoader<Settings>>()
+            .init_resource::<Settings>()
+            .add_startup_system(setup)
+            .add_system_to_stage(CoreStage::PreUpdate, apply);
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CurrentSettings::new(asset_server.load(SETTINGS_PATH)));
+}
+
+#[derive(Resource)]
+struct CurrentSettings {
+    handle: Handle<Settings>,
+}
+
+impl CurrentSettings {
+    fn new(handle: Handle<Settings>) -> Self {
+        Self { handle }
+    }
+}
+
+#[derive(Resource, TypeUuid, Clone, Deserialize)]
+#[uuid = "0b9d3c57-8a2e-4f61-b7d4-5e1c9a3f2d86"]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: Vec<Bindings>,
+    pub rules: Rules,
+    pub move_buffer_size: usize,
+    pub move_buffer_lifetime: f32,
+    pub move_motion: Motion,
+    pub extra_health: u32,
+    pub telegraph: bool,
+    pub auto_pause: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bindings: vec![Bindings::default()],
+            rules: Rules::Coop,
+            move_buffer_size: 3,
+            move_buffer_lifetime: 0.5,
+            move_motion: Motion {
+                style: MotionStyle::Hop(0.25),
+                duration: 0.12,
+                easing: Easing::QuadOut,
+            },
+            extra_health: 0,
+            telegraph: true,
+            auto_pause: true,
+        }
+    }
+}
+
+fn apply(
+    mut events: EventReader<AssetEvent<Settings>>,
+    mut settings: ResMut<Settings>,
+    current_settings: Res<CurrentSettings>,
+    assets: Res<Assets<Settings>>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle == current_settings.handle {
+            *settings = assets.get(handle).unwrap().clone();
+        }
+    }
+}
+
+impl RonAsset for Settings {
+    type Data = Self;
+    const EXTENSIONS: &'static [&'static str] = &["settings.ron"];
+
+    fn build(data: Self) -> Result<Self, String> {
+        // the lifetime becomes a `Duration`, which panics on these
+        let lifetime = data.move_buffer_lifetime;
+        match lifetime.is_finite() && lifetime >= 0.0 {
+            true => Ok(data),
+            false => Err(format!(
+                "move buffer lifetime must be a finite, non-negative number of seconds, got {}",
+                lifetime
+            )),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+use crate::{easing::Easing, phases::Phase};
+
+// absorbs the rounding of adding up phases defined in seconds
+pub const TOLERANCE: Duration = Duration::from_millis(1);
+
+pub trait Idle {
+    fn idle() -> Self;
+}
+
+pub struct Timeline<T> {
+    phases: Vec<Phase<T>>,
+    end: Duration,
+    error: Option<String>,
+}
+
+impl<T> Default for Timeline<T> {
+    fn default() -> Self {
+        Self {
+            phases: Vec::new(),
+            end: Duration::ZERO,
+            error: None,
+        }
+    }
+}
+
+impl<T: Clone + Copy + Idle> Timeline<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(self, mode: T, seconds: f32) -> Self {
+        self.then_eased(mode, seconds, Easing::Linear)
+    }
+
+    pub fn then_eased(mut self, mode: T, seconds: f32, easing: Easing) -> Self {
+        let phase = Phase::new(mode, seconds).with_easing(easing);
+        self.end += phase.duration;
+        self.phases.push(phase);
+        self
+    }
+
+    pub fn wait(self, seconds: f32) -> Self {
+        self.then(T::idle(), seconds)
+    }
+
+    // waits until the time since the start of the timeline
+    pub fn at(mut self, seconds: f32) -> Self {
+        match self.until(seconds, "at") {
+            Some(gap) => self.then(T::idle(), gap.as_secs_f32()),
+            None => self,
+        }
+    }
+
+    // extends the last phase until the time since the start of the timeline
+    pub fn pad_to(mut self, seconds: f32) -> Self {
+        let Some(gap) = self.until(seconds, "pad_to") else {
+            return self;
+        };
+        match self.phases.last_mut() {
+            Some(phase) => {
+                phase.duration += gap;
+                self.end += gap;
+                self
+            }
+            None => self.then(T::idle(), gap.as_secs_f32()),
+        }
+    }
+
+    pub fn sequence(self, pattern: impl FnOnce(Self) -> Self) -> Self {
+        pattern(self)
+    }
+
+    pub fn repeat(self, times: usize, mut pattern: impl FnMut(Self) -> Self) -> Self {
+        (0..times).fold(self, |timeline, _| pattern(timeline))
+    }
+
+    pub fn steps(self, steps: &[Step<T>], patterns: &HashMap<String, Vec<Step<T>>>) -> Self {
+        self.nested_steps(steps, patterns, &mut Vec::new())
+    }
+
+    pub fn build(self) -> Result<Vec<Phase<T>>, String> {
+        match (self.error, self.phases.is_empty()) {
+            (Some(error), _) => Err(error),
+            (None, true) => Err("the timeline is empty".to_string()),
+            (None, false) => Ok(self.phases),
+        }
+    }
+
+    fn until(&mut self, seconds: f32, step: &str) -> Option<Duration> {
+        let target = Duration::from_secs_f32(seconds);
+        if self.end > target + TOLERANCE && self.error.is_none() {
+            let end = self.end.as_secs_f32();
+            self.error = Some(format!("{step}({seconds}) is before the end ({end:.2}s)"));
+        }
+        self.error
+            .is_none()
+            .then(|| target.saturating_sub(self.end))
+    }
+
+    fn nested_steps<'a>(
+        mut self,
+        steps: &'a [Step<T>],
+        patterns: &'a HashMap<String, Vec<Step<T>>>,
+        active: &mut Vec<&'a str>,
+    ) -> Self {
+        for step in steps {
+            self = match step {
+                Step::Phase(mode, seconds) => self.then(*mode, *seconds),
+                Step::Eased(mode, seconds, easing) => self.then_eased(*mode, *seconds, *easing),
+                Step::Wait(seconds) => self.wait(*seconds),
+                Step::At(seconds) => self.at(*seconds),
+                Step::PadTo(seconds) => self.pad_to(*seconds),
+                Step::Repeat(times, steps) => self.repeat(*times, |timeline| {
+                    timeline.nested_steps(steps, patterns, active)
+                }),
+                Step::Pattern(name) => {
+                    let Some(pattern) = patterns.get(name) else {
+                        return self.fail(format!("unknown pattern `{name}`"));
+                    };
+                    if active.contains(&name.as_str()) {
+                        return self.fail(format!("pattern `{name}` contains itself"));
+                    }
+                    active.push(name);
+                    let timeline =
+                        self.sequence(|timeline| timeline.nested_steps(pattern, patterns, active));
+                    active.pop();
+                    timeline
+                }
+            };
+        }
+        self
+    }
+
+    fn fail(mut self, error: String) -> Self {
+        self.error = self.error.or(Some(error));
+        self
+    }
+}
+
+#[derive(Deserialize)]
+pub enum Step<T> {
+    Phase(T, f32),
+    Eased(T, f32, Easing),
+    Wait(f32),
+    At(f32),
+    PadTo(f32),
+    Repeat(usize, Vec<Step<T>>),
+    Pattern(String),
+}
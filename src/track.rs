@@ -0,0 +1,251 @@
+use std::hash::Hash;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    background::{self, Seek},
+    easing::Easing,
+    phases::{self, Phase, Phases, PhasesPlugin},
+    trial::{CurrentTrial, Trial},
+    AppState, AMBIENT_BRIGHTNESS,
+};
+
+pub struct TrackPlugin;
+
+impl Plugin for TrackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(PhasesPlugin::<Segment>::default())
+            .init_resource::<Choreography>()
+            .add_system_set(SystemSet::on_enter(AppState::Start).with_system(enter_start))
+            .add_system_set(
+                SystemSet::on_update(AppState::Game)
+                    .with_system(
+                        seek.after(background::seek)
+                            .before(phases::transition::<Segment>),
+                    )
+                    .with_system(animate::<FloatProperty>.after(phases::transition::<Segment>))
+                    .with_system(animate::<ColorProperty>.after(phases::transition::<Segment>))
+                    .with_system(animate::<VectorProperty>.after(phases::transition::<Segment>)),
+            )
+            .add_system(
+                mix.after(animate::<FloatProperty>)
+                    .after(animate::<ColorProperty>)
+                    .after(animate::<VectorProperty>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Teardown).with_system(enter_teardown));
+    }
+}
+
+#[derive(Resource)]
+pub struct Choreography {
+    pub aberration: f32,
+    pub fog: f32,
+    pub ambient: f32,
+    pub ambient_color: Color,
+    pub camera: Vec3,
+}
+
+impl Default for Choreography {
+    fn default() -> Self {
+        Self {
+            aberration: 0.0,
+            fog: 1.0,
+            ambient: AMBIENT_BRIGHTNESS,
+            ambient_color: Color::WHITE,
+            camera: Vec3::ZERO,
+        }
+    }
+}
+
+pub trait Interpolate: Copy + Send + Sync + 'static {
+    fn interpolate(self, other: Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec3 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        let [r, g, b, a] = self.as_rgba_f32();
+        let [other_r, other_g, other_b, other_a] = other.as_rgba_f32();
+        Color::rgba(
+            r.interpolate(other_r, t),
+            g.interpolate(other_g, t),
+            b.interpolate(other_b, t),
+            a.interpolate(other_a, t),
+        )
+    }
+}
+
+pub trait Property: Copy + Eq + Hash + Send + Sync + 'static {
+    type Value: Interpolate;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum FloatProperty {
+    Aberration,
+    Fog,
+    Ambient,
+}
+
+impl Property for FloatProperty {
+    type Value = f32;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum ColorProperty {
+    Ambient,
+}
+
+impl Property for ColorProperty {
+    type Value = Color;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum VectorProperty {
+    Camera,
+}
+
+impl Property for VectorProperty {
+    type Value = Vec3;
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Keyframe<V> {
+    pub time: f32,
+    pub value: V,
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Tracks {
+    pub floats: HashMap<FloatProperty, Vec<Keyframe<f32>>>,
+    pub colors: HashMap<ColorProperty, Vec<Keyframe<Color>>>,
+    pub vectors: HashMap<VectorProperty, Vec<Keyframe<Vec3>>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Segment(usize);
+
+#[derive(Component)]
+pub struct Track<P: Property> {
+    pub property: P,
+    pub keyframes: Vec<Keyframe<P::Value>>,
+    pub value: P::Value,
+}
+
+impl<P: Property> Track<P> {
+    pub fn new(property: P, keyframes: Vec<Keyframe<P::Value>>) -> Self {
+        let value = keyframes[0].value;
+        Self {
+            property,
+            keyframes,
+            value,
+        }
+    }
+
+    fn segments(&self) -> Vec<Phase<Segment>> {
+        let mut start = 0.0;
+        let mut phases = Vec::new();
+        for (i, keyframe) in self.keyframes.iter().enumerate() {
+            phases.push(Phase::new(Segment(i), keyframe.time - start).with_easing(keyframe.easing));
+            start = keyframe.time;
+        }
+        phases.push(Phase::new(Segment(self.keyframes.len()), 0.0)); // final
+        phases
+    }
+
+    fn sample(&self, segment: Segment, progress: f32) -> P::Value {
+        match segment.0 {
+            0 => self.keyframes[0].value,
+            i if i < self.keyframes.len() => {
+                let (from, to) = (&self.keyframes[i - 1], &self.keyframes[i]);
+                from.value.interpolate(to.value, progress)
+            }
+            _ => self.keyframes[self.keyframes.len() - 1].value,
+        }
+    }
+}
+
+fn spawn_tracks<P: Property>(
+    commands: &mut Commands,
+    tracks: &HashMap<P, Vec<Keyframe<P::Value>>>,
+) {
+    for (property, keyframes) in tracks {
+        let track = Track::new(*property, keyframes.clone());
+        let mut phases = Phases::new(Segment(0));
+        phases.reset(track.segments());
+        commands.spawn((track, phases));
+    }
+}
+
+fn enter_start(
+    mut commands: Commands,
+    current_trial: Res<CurrentTrial>,
+    trials: Res<Assets<Trial>>,
+) {
+    let trial = trials.get(&current_trial.handle).unwrap();
+    // the loader guarantees that every track has keyframes in order
+    spawn_tracks(&mut commands, &trial.tracks.floats);
+    spawn_tracks(&mut commands, &trial.tracks.colors);
+    spawn_tracks(&mut commands, &trial.tracks.vectors);
+}
+
+fn enter_teardown(mut commands: Commands, query: Query<Entity, With<Phases<Segment>>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn seek(mut events: EventReader<Seek>, mut query: Query<&mut Phases<Segment>>) {
+    for event in events.iter() {
+        for mut phases in &mut query {
+            phases.seek(event.time);
+        }
+    }
+}
+
+fn animate<P: Property>(mut query: Query<(&mut Track<P>, &Phases<Segment>)>) {
+    for (mut track, phases) in &mut query {
+        track.value = track.sample(phases.mode(), phases.eased_progress());
+    }
+}
+
+pub fn mix(
+    float_query: Query<&Track<FloatProperty>>,
+    color_query: Query<&Track<ColorProperty>>,
+    vector_query: Query<&Track<VectorProperty>>,
+    mut choreography: ResMut<Choreography>,
+) {
+    let mut mixed = Choreography::default();
+    for track in &float_query {
+        match track.property {
+            FloatProperty::Aberration => mixed.aberration = track.value,
+            FloatProperty::Fog => mixed.fog = track.value,
+            FloatProperty::Ambient => mixed.ambient = track.value,
+        }
+    }
+    for track in &color_query {
+        match track.property {
+            ColorProperty::Ambient => mixed.ambient_color = track.value,
+        }
+    }
+    for track in &vector_query {
+        match track.property {
+            VectorProperty::Camera => mixed.camera = track.value,
+        }
+    }
+    *choreography = mixed;
+}
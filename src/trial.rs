@@ -0,0 +1,292 @@
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    board::{Board, BoardMode},
+    bomb::Blast,
+    intensity::Stage,
+    laser::{Axis, LaserColors, LaserMode, Tracking},
+    loader::{RonAsset, RonLoader},
+    phases::Phase,
+    timeline::{Step, Timeline},
+    track::Tracks,
+    validation,
+};
+
+pub struct TrialPlugin;
+
+impl Plugin for TrialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Trial>()
+            .init_asset_loader::<RonLoader<Trial>>();
+    }
+}
+
+#[derive(Resource)]
+pub struct CurrentTrial {
+    pub index: usize,
+    pub handle: Handle<Trial>,
+}
+
+impl CurrentTrial {
+    pub fn new(index: usize, handle: Handle<Trial>) -> Self {
+        Self { index, handle }
+    }
+}
+
+#[derive(TypeUuid)]
+#[uuid = "6f1c2a8e-4d3b-4f5a-9c1e-2b7d8e9f0a13"]
+pub struct Trial {
+    pub name: String,
+    pub duration: f32,
+    pub board: Board,
+    pub health: HealthDefinition,
+    pub dash: Option<DashDefinition>,
+    pub lasers: Vec<LaserDefinition>,
+    pub bombs: Vec<BombDefinition>,
+    pub timelines: HashMap<String, Timelines>,
+    pub tracks: Tracks,
+    pub stages: Vec<Stage>,
+    pub music: Vec<String>,
+}
+
+impl TryFrom<TrialData> for Trial {
+    type Error = String;
+
+    fn try_from(data: TrialData) -> Result<Self, Self::Error> {
+        seconds(data.duration).map_err(|error| format!("duration {}", error))?;
+        if data.health.points < 1 {
+            return Err("health points must be at least 1".to_string());
+        }
+        seconds(data.health.invulnerability)
+            .map_err(|error| format!("health invulnerability {}", error))?;
+        if let Some(dash) = &data.dash {
+            if dash.distance < 1 {
+                return Err("dash distance must be at least 1".to_string());
+            }
+            seconds(dash.cooldown).map_err(|error| format!("dash cooldown {}", error))?;
+        }
+        for (i, laser) in data.lasers.iter().enumerate() {
+            if data.lasers[..i].iter().any(|other| other.id == laser.id) {
+                return Err(format!("duplicated laser id `{}`", laser.id));
+            }
+            let straight = matches!(laser.axis, Axis::Horizontal | Axis::Vertical);
+            if laser.mobile && (!straight || laser.sweep.is_some()) {
+                return Err(format!(
+                    "mobile laser `{}` must keep to a row or column",
+                    laser.id
+                ));
+            }
+            if !data.timelines.contains_key(&laser.id) {
+                return Err(format!("missing timelines for laser `{}`", laser.id));
+            }
+        }
+        for (i, bomb) in data.bombs.iter().enumerate() {
+            let lasers = data.lasers.iter().map(|laser| &laser.id);
+            let bombs = data.bombs[..i].iter().map(|other| &other.id);
+            if lasers.chain(bombs).any(|id| *id == bomb.id) {
+                return Err(format!("duplicated bomb id `{}`", bomb.id));
+            }
+            let Some(steps) = data.timelines.get(&bomb.id) else {
+                return Err(format!("missing timelines for bomb `{}`", bomb.id));
+            };
+            if !steps.board.is_empty() {
+                return Err(format!("bomb `{}` can't have a board timeline", bomb.id));
+            }
+            if !data.board.tiles.contains(&bomb.position) {
+                return Err(format!("bomb `{}` is off the board", bomb.id));
+            }
+        }
+        if data
+            .stages
+            .windows(2)
+            .any(|pair| pair[0].time > pair[1].time)
+        {
+            return Err("stages must be in order of time".to_string());
+        }
+        for (name, steps) in &data.patterns.board {
+            step_seconds(steps).map_err(|error| format!("board pattern `{}`: {}", name, error))?;
+        }
+        for (name, steps) in &data.patterns.laser {
+            step_seconds(steps).map_err(|error| format!("laser pattern `{}`: {}", name, error))?;
+        }
+        let floats = data.tracks.floats.values().flatten().map(|key| key.time);
+        let colors = data.tracks.colors.values().flatten().map(|key| key.time);
+        let vectors = data.tracks.vectors.values().flatten().map(|key| key.time);
+        for time in floats.chain(colors).chain(vectors) {
+            seconds(time).map_err(|error| format!("keyframe time {}", error))?;
+        }
+        let mut timelines = HashMap::new();
+        for (id, steps) in &data.timelines {
+            step_seconds(&steps.board)
+                .map_err(|error| format!("board timeline of `{}`: {}", id, error))?;
+            step_seconds(&steps.laser)
+                .map_err(|error| format!("laser timeline of `{}`: {}", id, error))?;
+            // bombs only follow their laser timeline
+            let board = match data.bombs.iter().any(|bomb| bomb.id == *id) {
+                true => Vec::new(),
+                false => Timeline::new()
+                    .steps(&steps.board, &data.patterns.board)
+                    .build()
+                    .map_err(|error| format!("board timeline of `{}`: {}", id, error))?,
+            };
+            let laser = Timeline::new()
+                .steps(&steps.laser, &data.patterns.laser)
+                .build()
+                .map_err(|error| format!("laser timeline of `{}`: {}", id, error))?;
+            timelines.insert(id.clone(), Timelines { board, laser });
+        }
+        Ok(Self {
+            name: data.name,
+            duration: data.duration,
+            board: data.board,
+            health: data.health,
+            dash: data.dash,
+            lasers: data.lasers,
+            bombs: data.bombs,
+            timelines,
+            tracks: data.tracks,
+            stages: data.stages,
+            music: data.music,
+        })
+    }
+}
+
+// `Duration` panics on these, so they never reach the timelines or validation
+fn seconds(seconds: f32) -> Result<(), String> {
+    match seconds.is_finite() && seconds >= 0.0 {
+        true => Ok(()),
+        false => Err(format!(
+            "must be a finite, non-negative number of seconds, got {}",
+            seconds
+        )),
+    }
+}
+
+fn step_seconds<T>(steps: &[Step<T>]) -> Result<(), String> {
+    for step in steps {
+        match step {
+            Step::Phase(_, time)
+            | Step::Eased(_, time, _)
+            | Step::Wait(time)
+            | Step::At(time)
+            | Step::PadTo(time) => seconds(*time)?,
+            Step::Repeat(_, steps) => step_seconds(steps)?,
+            Step::Pattern(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct LaserDefinition {
+    pub id: String,
+    pub position: IVec2,
+    pub axis: Axis,
+    #[serde(default)]
+    pub sweep: Option<Axis>,
+    #[serde(default)]
+    pub mobile: bool,
+    #[serde(default)]
+    pub tracking: Tracking,
+    #[serde(default)]
+    pub colors: LaserColors,
+}
+
+// the laser timeline of the bomb lights the fuse while `Charging` and blasts while `Attacking`
+#[derive(Deserialize)]
+pub struct BombDefinition {
+    pub id: String,
+    pub position: IVec2,
+    #[serde(default)]
+    pub blast: Blast,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct HealthDefinition {
+    pub points: u32,
+    pub invulnerability: f32,
+}
+
+impl Default for HealthDefinition {
+    fn default() -> Self {
+        Self {
+            points: 1,
+            invulnerability: 1.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct DashDefinition {
+    pub distance: i32,
+    pub cooldown: f32,
+}
+
+impl Default for DashDefinition {
+    fn default() -> Self {
+        Self {
+            distance: 2,
+            cooldown: 2.0,
+        }
+    }
+}
+
+pub struct Timelines {
+    // empty for bombs
+    pub board: Vec<Phase<BoardMode>>,
+    pub laser: Vec<Phase<LaserMode>>,
+}
+
+#[derive(Deserialize)]
+pub struct TrialData {
+    name: String,
+    duration: f32,
+    board: Board,
+    #[serde(default)]
+    health: HealthDefinition,
+    #[serde(default)]
+    dash: Option<DashDefinition>,
+    lasers: Vec<LaserDefinition>,
+    #[serde(default)]
+    bombs: Vec<BombDefinition>,
+    #[serde(default)]
+    patterns: Patterns,
+    timelines: HashMap<String, TimelinesData>,
+    #[serde(default)]
+    tracks: Tracks,
+    #[serde(default)]
+    stages: Vec<Stage>,
+    #[serde(default)]
+    music: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Patterns {
+    board: HashMap<String, Vec<Step<BoardMode>>>,
+    laser: HashMap<String, Vec<Step<LaserMode>>>,
+}
+
+#[derive(Deserialize)]
+struct TimelinesData {
+    #[serde(default)]
+    board: Vec<Step<BoardMode>>,
+    laser: Vec<Step<LaserMode>>,
+}
+
+impl RonAsset for Trial {
+    type Data = TrialData;
+    const EXTENSIONS: &'static [&'static str] = &["trial.ron"];
+
+    fn build(data: TrialData) -> Result<Self, String> {
+        let trial = Trial::try_from(data)?;
+        let issues = validation::validate(&trial);
+        match issues.is_empty() {
+            true => Ok(trial),
+            false => Err(issues.join("; ")),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use std::{fmt::Debug, time::Duration};
+
+use bevy::utils::HashMap;
+
+use crate::{
+    board::BoardMode,
+    laser::{Axis, LaserMode},
+    phases::Phase,
+    timeline::TOLERANCE,
+    track::Keyframe,
+    trial::Trial,
+};
+
+pub fn validate(trial: &Trial) -> Vec<String> {
+    let mut issues = Vec::new();
+    let duration = Duration::from_secs_f32(trial.duration);
+    for laser in &trial.lasers {
+        let timelines = &trial.timelines[&laser.id];
+        let board_spans = spans(&timelines.board);
+        let laser_spans = spans(&timelines.laser);
+        // built timelines are never empty
+        if let Some(issue) = check_total(board_spans.last().unwrap(), duration) {
+            issues.push(format!("board timeline of `{}` {}", laser.id, issue));
+        }
+        if let Some(issue) = check_total(laser_spans.last().unwrap(), duration) {
+            issues.push(format!("laser timeline of `{}` {}", laser.id, issue));
+        }
+        for laser_span in &laser_spans {
+            if !matches!(laser_span.mode, LaserMode::Attacking) {
+                continue;
+            }
+            for board_span in &board_spans {
+                if matches!(board_span.mode, BoardMode::Shown) {
+                    continue;
+                }
+                if let Some(start) = laser_span.overlap(board_span) {
+                    issues.push(format!(
+                        "laser `{}` attacks at {:.2}s while {:?}",
+                        laser.id,
+                        start.as_secs_f32(),
+                        board_span.mode,
+                    ));
+                }
+            }
+        }
+    }
+    for bomb in &trial.bombs {
+        let laser_spans = spans(&trial.timelines[&bomb.id].laser);
+        if let Some(issue) = check_total(laser_spans.last().unwrap(), duration) {
+            issues.push(format!("laser timeline of `{}` {}", bomb.id, issue));
+        }
+    }
+    // static lasers on the same line must take turns to be on the board
+    for (i, laser) in trial.lasers.iter().enumerate() {
+        for other in &trial.lasers[..i] {
+            let (position, other_position) = (laser.position, other.position);
+            let same_line = match (laser.axis, other.axis) {
+                (Axis::Horizontal, Axis::Horizontal) => position.y == other_position.y,
+                (Axis::Vertical, Axis::Vertical) => position.x == other_position.x,
+                (Axis::Diagonal, Axis::Diagonal) => {
+                    position.x - position.y == other_position.x - other_position.y
+                }
+                (Axis::AntiDiagonal, Axis::AntiDiagonal) => {
+                    position.x + position.y == other_position.x + other_position.y
+                }
+                _ => false,
+            };
+            let sweeping = laser.sweep.is_some() || other.sweep.is_some();
+            if !same_line || sweeping || laser.mobile || other.mobile {
+                continue;
+            }
+            let laser_spans = presence(&trial.timelines[&laser.id].board);
+            let other_spans = presence(&trial.timelines[&other.id].board);
+            for span in &laser_spans {
+                for other_span in &other_spans {
+                    if let Some(start) = span.overlap(other_span) {
+                        issues.push(format!(
+                            "lasers `{}` and `{}` overlap on the board at {:.2}s",
+                            other.id,
+                            laser.id,
+                            start.as_secs_f32(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    check_tracks(&trial.tracks.floats, duration, &mut issues);
+    check_tracks(&trial.tracks.colors, duration, &mut issues);
+    check_tracks(&trial.tracks.vectors, duration, &mut issues);
+    issues
+}
+
+fn check_tracks<P: Debug, V>(
+    tracks: &HashMap<P, Vec<Keyframe<V>>>,
+    duration: Duration,
+    issues: &mut Vec<String>,
+) {
+    for (property, keyframes) in tracks {
+        if let Some(issue) = check_keyframes(keyframes, duration) {
+            issues.push(format!("{:?} track {}", property, issue));
+        }
+    }
+}
+
+fn check_keyframes<V>(keyframes: &[Keyframe<V>], duration: Duration) -> Option<String> {
+    let duration_secs = duration.as_secs_f32();
+    let mut previous = 0.0;
+    for keyframe in keyframes {
+        let time = keyframe.time;
+        if time < previous {
+            return Some(format!("goes back in time at {:.2}s", time));
+        }
+        if Duration::from_secs_f32(time) > duration + TOLERANCE {
+            return Some(format!(
+                "has a keyframe at {:.2}s, after the trial ({:.2}s)",
+                time, duration_secs
+            ));
+        }
+        previous = time;
+    }
+    keyframes.is_empty().then(|| "has no keyframes".to_string())
+}
+
+fn presence(phases: &[Phase<BoardMode>]) -> Vec<Span<()>> {
+    let mut merged: Vec<Span<()>> = Vec::new();
+    for span in spans(phases) {
+        if matches!(span.mode, BoardMode::Hidden | BoardMode::Waiting) {
+            continue;
+        }
+        match merged.last_mut() {
+            Some(last) if last.start + last.duration == span.start => {
+                last.duration += span.duration;
+                last.last = span.last;
+            }
+            _ => merged.push(Span {
+                mode: (),
+                start: span.start,
+                duration: span.duration,
+                last: span.last,
+            }),
+        }
+    }
+    merged
+}
+
+// timelines either last as long as the trial or rest in a final phase
+fn check_total<T>(last: &Span<T>, duration: Duration) -> Option<String> {
+    let total = last.start + last.duration;
+    let (total_secs, duration_secs) = (total.as_secs_f32(), duration.as_secs_f32());
+    if total > duration + TOLERANCE {
+        Some(format!(
+            "ends at {:.2}s, after the trial ({:.2}s)",
+            total_secs, duration_secs
+        ))
+    } else if total + TOLERANCE < duration && last.duration > Duration::ZERO {
+        Some(format!(
+            "ends at {:.2}s, before the trial ({:.2}s), without a final phase",
+            total_secs, duration_secs
+        ))
+    } else {
+        None
+    }
+}
+
+struct Span<T> {
+    mode: T,
+    start: Duration,
+    duration: Duration,
+    last: bool,
+}
+
+impl<T> Span<T> {
+    fn end(&self) -> Option<Duration> {
+        // the last phase lasts until the timeline is reset
+        match self.last {
+            true => None,
+            false => Some(self.start + self.duration),
+        }
+    }
+
+    fn overlap<U>(&self, other: &Span<U>) -> Option<Duration> {
+        let start = self.start.max(other.start);
+        let end = match (self.end(), other.end()) {
+            (Some(end), Some(other_end)) => Some(end.min(other_end)),
+            (end, None) | (None, end) => end,
+        };
+        match end {
+            Some(end) if end <= start + TOLERANCE => None,
+            _ => Some(start),
+        }
+    }
+}
+
+fn spans<T: Copy>(phases: &[Phase<T>]) -> Vec<Span<T>> {
+    let mut start = Duration::ZERO;
+    let mut spans = Vec::new();
+    for (i, phase) in phases.iter().enumerate() {
+        spans.push(Span {
+            mode: phase.mode,
+            start,
+            duration: phase.duration,
+            last: i + 1 == phases.len(),
+        });
+        start += phase.duration;
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trial::TrialData;
+
+    fn trial(source: &str) -> Trial {
+        let data = ron::from_str::<TrialData>(source).unwrap();
+        Trial::try_from(data).unwrap()
+    }
+
+    fn issues(source: &str) -> Vec<String> {
+        validate(&trial(source))
+    }
+
+    #[test]
+    fn bundled_trials() {
+        assert!(issues(include_str!("../assets/trials/first.trial.ron")).is_empty());
+        assert!(issues(include_str!("../assets/trials/second.trial.ron")).is_empty());
+        assert!(issues(include_str!("../assets/trials/third.trial.ron")).is_empty());
+    }
+
+    #[test]
+    fn wrong_total() {
+        let issues = issues(
+            r##"(
+                name: "test",
+                duration: 10.0,
+                board: ["#"],
+                lasers: [(id: "laser", position: (0, 0), axis: Horizontal)],
+                timelines: {
+                    "laser": (
+                        board: [Phase(Shown, 0.0)],
+                        laser: [Phase(Charging, 1.0), Phase(Attacking, 1.0)],
+                    ),
+                },
+            )"##,
+        );
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("ends at 2.00s"), "{}", issues[0]);
+    }
+
+    #[test]
+    fn attack_while_hidden() {
+        let issues = issues(
+            r##"(
+                name: "test",
+                duration: 10.0,
+                board: ["#"],
+                lasers: [(id: "laser", position: (0, 0), axis: Horizontal)],
+                timelines: {
+                    "laser": (
+                        board: [Wait(2.0), Phase(Shown, 0.0)],
+                        laser: [At(1.0), Phase(Attacking, 0.5), Wait(0.0)],
+                    ),
+                },
+            )"##,
+        );
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("at 1.00s while Hidden"), "{}", issues[0]);
+    }
+
+    #[test]
+    fn overlap_on_the_same_line() {
+        let issues = issues(
+            r##"(
+                name: "test",
+                duration: 10.0,
+                board: ["#"],
+                lasers: [
+                    (id: "first", position: (0, 0), axis: Horizontal),
+                    (id: "second", position: (0, 0), axis: Horizontal),
+                ],
+                timelines: {
+                    "first": (
+                        board: [At(1.0), Phase(Shown, 0.0)],
+                        laser: [Wait(0.0)],
+                    ),
+                    "second": (
+                        board: [At(3.0), Phase(Shown, 0.0)],
+                        laser: [Wait(0.0)],
+                    ),
+                },
+            )"##,
+        );
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("on the board at 3.00s"), "{}", issues[0]);
+    }
+
+    #[test]
+    fn negative_seconds() {
+        let data = ron::from_str::<TrialData>(
+            r##"(
+                name: "test",
+                duration: 10.0,
+                board: ["#"],
+                lasers: [(id: "laser", position: (0, 0), axis: Horizontal)],
+                timelines: {
+                    "laser": (laser: [Wait(-1.0), PadTo(10.0)]),
+                },
+            )"##,
+        )
+        .unwrap();
+        let error = Trial::try_from(data).err().unwrap();
+        assert!(error.contains("got -1"), "{}", error);
+    }
+}